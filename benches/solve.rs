@@ -0,0 +1,147 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use sudoku_solver::{Grid, SolveError, SolverConfig};
+
+/// Puzzles of increasing difficulty, as a name and an 81-character grid string.
+const PUZZLES: [(&str, &str); 5] = [
+    ("easy", "000500000630000001000263749060900530000075000003008900040030000002050060500000010"),
+    ("hard", "400001309000000006010800000000040000005000070000209004068900500200007080500000001"),
+    ("17_clue", "000000010400000000020000000000050407008000300001090000300400200050100000000806000"),
+    ("guessing", "800000000003600000070090200050007000000045700000100030001000068008500010090000400"),
+    ("platinum_blonde", "000000012000000003002300400001800005060070800000009000008500000900040500470006000"),
+];
+
+/// Well-known hard puzzles that require guessing, as a name and an 81-character grid string.
+const HARD: [(&str, &str); 6] = [
+    ("ai_escargot", "100007090030020008009600500005300900010080002600004000300000010040000007007000300"),
+    ("inkala_2012", "800000000003600000070090200050007000000045700000100030001000068008500010090000400"),
+    ("platinum_blonde", "000000012000000003002300400001800005060070800000009000008500000900040500470006000"),
+    ("golden_nugget", "000000039000001005003050800008090006070002000100400000009080050020000600400700000"),
+    ("easter_monster", "100000002090400050006000700050903000000070000000850040700000600030009080002000001"),
+    ("kolk", "120400300300010050006000100700090000040603000003002000500080700007000005000000098"),
+];
+
+fn solve(c: &mut Criterion) {
+    let mut group = c.benchmark_group("solve");
+    for (name, puzzle) in PUZZLES {
+        let grid = Grid::try_from_str(puzzle).unwrap();
+        group.bench_function(name, |b| b.iter(|| {
+            let mut grid = black_box(&grid).clone();
+            grid.solve().unwrap();
+            grid
+        }));
+    }
+    group.finish();
+}
+
+/// Solve the grid by guessing on the first digit found with only two possible cells in a unit, trying each cell in
+/// turn, as the solver did before guessing on the cell with the fewest candidates. This serves as the baseline for
+/// `guesses`, with `guesses` incremented per digit tried in the same way as `SolveStats::guesses`.
+fn solve_by_pairs(grid: &Grid, guesses: &mut usize) -> Option<Grid> {
+    let mut grid = grid.clone();
+    match grid.solve_with(&SolverConfig::new().guessing(false)) {
+        Ok(()) => return Some(grid),
+        Err(SolveError::Stuck) => (),
+        Err(_) => return None,
+    }
+
+    let units = (0..9).flat_map(|i| [
+        core::array::from_fn::<_, 9, _>(|j| (j, i)),
+        core::array::from_fn(|j| (i, j)),
+        core::array::from_fn(|j| (i % 3 * 3 + j % 3, i / 3 * 3 + j / 3)),
+    ]);
+    let (digit, cells) = units.flat_map(|cells| (1..=9).map(move |digit| (digit, cells)))
+        .find(|&(digit, cells)| cells.iter().filter(|&&(x, y)| grid.candidates(x, y) & 1 << (digit - 1) != 0).count() == 2)
+        .expect("a puzzle that needs guessing has a digit with two possible cells in some unit");
+
+    for &(x, y) in cells.iter().filter(|&&(x, y)| grid.candidates(x, y) & 1 << (digit - 1) != 0) {
+        *guesses += 1;
+        let mut branch = grid.clone();
+        if branch.place(x, y, digit).is_ok() {
+            if let Some(solution) = solve_by_pairs(&branch, guesses) {
+                return Some(solution);
+            }
+        }
+    }
+    None
+}
+
+fn guesses(c: &mut Criterion) {
+    // Report the guesses made on each hard puzzle against the baseline, then time both approaches.
+    let mut group = c.benchmark_group("guesses");
+    for (name, puzzle) in HARD {
+        let grid = Grid::try_from_str(puzzle).unwrap();
+        let stats = grid.clone().solve_with_stats().unwrap();
+        let mut baseline = 0;
+        assert_eq!(solve_by_pairs(&grid, &mut baseline), Some(grid.solved().unwrap()));
+        println!("{}: {} guesses, {} guessing on pairs", name, stats.guesses, baseline);
+
+        group.bench_function(format!("{}/fewest_candidates", name), |b| b.iter(|| {
+            let mut grid = black_box(&grid).clone();
+            grid.solve().unwrap();
+            grid
+        }));
+        group.bench_function(format!("{}/pairs", name), |b| b.iter(|| {
+            solve_by_pairs(black_box(&grid), &mut 0).unwrap()
+        }));
+    }
+    group.finish();
+}
+
+/// Solve the grid by guessing on the cell with the fewest candidates, cloning the grid for each digit tried rather than
+/// undoing failed guesses in place as the solver does. `config` must disallow guessing, which is done here instead.
+fn solve_by_cloning(grid: &Grid, config: &SolverConfig) -> Option<Grid> {
+    let mut grid = grid.clone();
+    match grid.solve_with(config) {
+        Ok(()) => return Some(grid),
+        Err(SolveError::Stuck) => (),
+        Err(_) => return None,
+    }
+
+    let (x, y) = (0..81).map(|i| (i % 9, i / 9))
+        .filter(|&(x, y)| grid.candidates(x, y) != 0)
+        .min_by_key(|&(x, y)| grid.candidates(x, y).count_ones())?;
+    grid.candidate_digits(x, y).find_map(|digit| {
+        let mut branch = grid.clone();
+        branch.place(x, y, digit).ok()?;
+        solve_by_cloning(&branch, config)
+    })
+}
+
+fn undo(c: &mut Criterion) {
+    // Without techniques, the 17-clue puzzle takes thousands of guesses, timing how failed guesses are undone.
+    let config = SolverConfig::new().without_techniques();
+    let (_, puzzle) = PUZZLES[2];
+    let grid = Grid::try_from_str(puzzle).unwrap();
+    assert_eq!(solve_by_cloning(&grid, &config.guessing(false)), Some(grid.solved().unwrap()));
+
+    let mut group = c.benchmark_group("undo");
+    group.bench_function("17_clue/in_place", |b| b.iter(|| {
+        let mut grid = black_box(&grid).clone();
+        grid.solve_with(&config).unwrap();
+        grid
+    }));
+    group.bench_function("17_clue/cloning", |b| b.iter(|| {
+        solve_by_cloning(black_box(&grid), &config.guessing(false)).unwrap()
+    }));
+    group.finish();
+}
+
+#[cfg(feature = "rayon")]
+fn solve_parallel(c: &mut Criterion) {
+    let mut group = c.benchmark_group("solve_parallel");
+    for (name, puzzle) in PUZZLES {
+        let grid = Grid::try_from_str(puzzle).unwrap();
+        group.bench_function(name, |b| b.iter(|| {
+            let mut grid = black_box(&grid).clone();
+            grid.solve_parallel().unwrap();
+            grid
+        }));
+    }
+    group.finish();
+}
+
+#[cfg(not(feature = "rayon"))]
+criterion_group!(benches, solve, guesses, undo);
+#[cfg(feature = "rayon")]
+criterion_group!(benches, solve, guesses, undo, solve_parallel);
+criterion_main!(benches);
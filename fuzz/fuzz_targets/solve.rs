@@ -0,0 +1,39 @@
+//! Feeds arbitrary input through the parser and solver, which must never panic.
+//! Run with `cargo fuzz run solve` from the root of the repository.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use sudoku_solver::{Grid, SolveError};
+
+fuzz_target!(|data: &[u8]| {
+    // Arbitrary strings rarely parse, so the bytes are also read as digits to reach the solver more often.
+    if let Ok(str) = std::str::from_utf8(data) {
+        if let Ok(grid) = Grid::try_from_str(str) {
+            check(grid);
+        }
+    }
+    if data.len() >= 81 {
+        let digits: String = data[..81].iter().map(|&byte| char::from(b'0' + byte % 10)).collect();
+        check(Grid::try_from_str(&digits).expect("a string of 81 digits always parses"));
+    }
+});
+
+fn check(puzzle: Grid) {
+    //! Solve the puzzle, checking that any solution found is valid and keeps the digits of the puzzle.
+
+    let mut grid = puzzle.clone();
+    let result = grid.solve_checked();
+    match result {
+        Ok(()) | Err(SolveError::NotUnique) => {
+            assert!(grid.is_valid_solution(), "invalid solution {} of {}", grid.to_str(), puzzle.to_str());
+            let kept = puzzle.to_str().chars().zip(grid.to_str().chars()).all(|(given, digit)| given == '0' || given == digit);
+            assert!(kept, "solution {} does not keep the digits of {}", grid.to_str(), puzzle.to_str());
+        },
+        Err(_) => (),
+    }
+
+    // The plain solver agrees on whether a solution exists.
+    let mut solved = puzzle.clone();
+    assert_eq!(solved.solve().is_ok(), !matches!(result, Err(SolveError::Contradiction { .. })));
+}
@@ -0,0 +1,48 @@
+//! C interface to the solver, enabled by the `ffi` feature.
+//! 
+//! A static or dynamic library exposing it can be built with, for example,
+//! `cargo rustc --release --features ffi --crate-type staticlib`.
+
+use core::ffi::{c_char, c_int, CStr};
+use crate::Grid;
+
+/// Returned by `sudoku_solve` when the grid was solved.
+pub const SUDOKU_OK: c_int = 0;
+/// Returned by `sudoku_solve` when the grid has no solution.
+pub const SUDOKU_NO_SOLUTION: c_int = 1;
+/// Returned by `sudoku_solve` when a pointer was null or the input was not a valid grid string.
+pub const SUDOKU_INVALID_INPUT: c_int = 2;
+
+/// Solves the nul-terminated 81-character grid string `in81`, as per `Grid::try_from_str`, writing the
+/// 81 ASCII digits of the solution to `out81`. No nul terminator is written.
+/// 
+/// Returns `SUDOKU_OK` on success, otherwise `SUDOKU_NO_SOLUTION` or `SUDOKU_INVALID_INPUT`,
+/// in which case `out81` is left untouched.
+/// 
+/// # Safety
+/// `in81` must be null or point to a nul-terminated string, and `out81` must be null or point to
+/// at least 81 writable bytes. The buffers may not overlap.
+#[no_mangle]
+pub unsafe extern "C" fn sudoku_solve(in81: *const c_char, out81: *mut c_char) -> c_int {
+    if in81.is_null() || out81.is_null() {
+        return SUDOKU_INVALID_INPUT;
+    }
+
+    // SAFETY: `in81` is non-null and nul-terminated, as guaranteed by the caller.
+    let Ok(input) = unsafe { CStr::from_ptr(in81) }.to_str() else {
+        return SUDOKU_INVALID_INPUT;
+    };
+    let Ok(mut grid) = Grid::try_from_str(input) else {
+        return SUDOKU_INVALID_INPUT;
+    };
+    if grid.solve().is_err() {
+        return SUDOKU_NO_SOLUTION;
+    }
+
+    // SAFETY: `out81` is non-null and points to at least 81 writable bytes, as guaranteed by the caller.
+    let out = unsafe { core::slice::from_raw_parts_mut(out81 as *mut u8, 81) };
+    for (byte, char) in out.iter_mut().zip(grid.to_str().chars()) {
+        *byte = char as u8;
+    }
+    SUDOKU_OK
+}
@@ -1,357 +1,5060 @@
 #![no_std]
+#![allow(clippy::needless_range_loop)]
 
+#[cfg(feature = "alloc")]
 extern crate alloc;
+#[cfg(not(any(feature = "alloc", feature = "fixed-stacks")))]
+compile_error!("the solver's stacks need the `alloc` feature, or the `fixed-stacks` feature to store them inline.");
+// Streaming puzzles requires std's I/O traits, as does the C interface for its panic handler when built standalone.
+#[cfg(feature = "std")]
+extern crate std;
 
+#[cfg(feature = "ffi")]
+pub mod ffi;
+mod stack;
+
+#[cfg(feature = "alloc")]
 use alloc::vec::Vec;
-use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+#[cfg(feature = "rayon")]
+use alloc::boxed::Box;
+use core::fmt;
+use rand_core::RngCore;
+use stack::{List, OpStack, Stack};
+
+/// Error returned when a grid could not be constructed from its input.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseError {
+    /// The input was not of the expected length, holding the length found instead.
+    InvalidLength(usize),
+    /// The grid string contained a character that is neither a digit nor a blank.
+    InvalidCharacter(char),
+    /// The cell at `(x, y)` held a value above the largest digit, 9 for a standard grid.
+    InvalidDigit { x: usize, y: usize, value: u8 },
+    /// The candidates of the cell at `(x, y)` were empty, out of range, or excluded the digit held there.
+    InvalidCandidates { x: usize, y: usize },
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            ParseError::InvalidLength(len) =>
+                write!(f, "grid input of unexpected length {}", len),
+            ParseError::InvalidCharacter(char) =>
+                write!(f, "unexpected character {:?} in grid string", char),
+            ParseError::InvalidDigit { x, y, value } =>
+                write!(f, "invalid cell value {} at ({}, {})", value, x, y),
+            ParseError::InvalidCandidates { x, y } =>
+                write!(f, "invalid candidates at ({}, {})", x, y),
+        }
+    }
+}
+
+/// Error returned when a grid could not be solved, see `Grid::solve` and `Grid::solve_checked`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveError {
+    /// The grid has no solution, its digits contradict each other.
+    /// 
+    /// `digit` could not be placed at `(x, y)`, or was the last digit possible there before it was eliminated.
+    /// Where guesses were made, this is the contradiction that ended the search. As the search is exhaustive,
+    /// this is only ever returned if no solution exists, never due to a limitation of the solver.
+    Contradiction { x: usize, y: usize, digit: u8 },
+    /// The grid has more than one solution.
+    NotUnique,
+    /// The grid could not be solved without guessing, which was not allowed, see `SolverConfig::guessing`.
+    Stuck,
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveError::Contradiction { x, y, digit } =>
+                write!(f, "grid has no solution, contradiction on digit {} at ({}, {})", digit, x, y),
+            SolveError::NotUnique => f.write_str("grid has more than one solution"),
+            SolveError::Stuck => f.write_str("grid could not be solved without guessing"),
+        }
+    }
+}
+
+/// Error returned when a grid could not be solved within its budget, see `Grid::solve_bounded`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveBudgetError {
+    /// The budget of steps ran out before the grid was solved, leaving the grid in an undefined state.
+    Exhausted,
+    /// The grid has no solution, see `SolveError::Contradiction`.
+    Contradiction { x: usize, y: usize, digit: u8 },
+}
+
+impl fmt::Display for SolveBudgetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SolveBudgetError::Exhausted => f.write_str("grid could not be solved within the budget of steps"),
+            SolveBudgetError::Contradiction { x, y, digit } =>
+                write!(f, "grid has no solution, contradiction on digit {} at ({}, {})", digit, x, y),
+        }
+    }
+}
+
+/// Error returned when a digit could not be placed or erased, see `Grid::place` and `Grid::erase`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaceError {
+    /// The cell at `(x, y)` is outside of the grid.
+    OutOfBounds { x: usize, y: usize },
+    /// The digit is outside of 1 to the largest digit, 9 for a standard grid.
+    InvalidDigit(u8),
+    /// The cell at `(x, y)` already holds a digit.
+    Occupied { x: usize, y: usize },
+    /// The cell at `(x, y)` is a given of the puzzle, and cannot be erased.
+    Given { x: usize, y: usize },
+    /// The digits of the grid contradict each other, see `SolveError::Contradiction`.
+    Contradiction { x: usize, y: usize, digit: u8 },
+}
+
+impl fmt::Display for PlaceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            PlaceError::OutOfBounds { x, y } =>
+                write!(f, "cell index ({}, {}) out of bounds", x, y),
+            PlaceError::InvalidDigit(digit) =>
+                write!(f, "invalid digit {}", digit),
+            PlaceError::Occupied { x, y } =>
+                write!(f, "cell at ({}, {}) already holds a digit", x, y),
+            PlaceError::Given { x, y } =>
+                write!(f, "cell at ({}, {}) is a given", x, y),
+            PlaceError::Contradiction { x, y, digit } =>
+                write!(f, "contradiction on digit {} at ({}, {})", digit, x, y),
+        }
+    }
+}
+
+/// Outcome of solving a grid by logic alone, see `Grid::solve_logical`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolveOutcome {
+    /// Every cell was filled in.
+    Solved,
+    /// No further logical progress can be made, solving the rest of the grid requires guessing.
+    Stuck,
+    /// The grid has no solution, see `SolveError::Contradiction`.
+    Contradiction { x: usize, y: usize, digit: u8 },
+}
+
+/// What a single step of the solver did, see `Grid::step`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepResult {
+    /// `digit` was eliminated as a candidate of the cell at `(x, y)`.
+    Eliminated { digit: u8, x: usize, y: usize },
+    /// `digit` was set at `(x, y)`.
+    Placed { digit: u8, x: usize, y: usize },
+    /// The technique found deductions, which are left pending for the following steps to make.
+    Applied(Technique),
+    /// The sums of killer cages ruled out candidates, which are left pending for the following steps to eliminate.
+    CageSums,
+    /// The order of thermometers ruled out candidates, which are left pending for the following steps to eliminate.
+    Thermometers,
+    /// No further logical progress can be made, solving the rest of the grid requires guessing.
+    NoProgress,
+    /// Every cell is filled in.
+    Solved,
+    /// The grid has no solution, see `SolveError::Contradiction`.
+    Contradiction { x: usize, y: usize, digit: u8 },
+}
+
+/// An additional rule the digits of a grid must satisfy, see `Grid::with_constraint`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Constraint {
+    /// Both main diagonals must contain each digit exactly once, as in X-Sudoku.
+    Diagonals,
+    /// The windows the size of a block a cell in from each block's corner must contain each digit exactly once,
+    /// as in windoku. These are the four shaded boxes of a standard grid. Windows only fit between blocks whose
+    /// width and height differ by at most one, such that adding them to other grids panics.
+    Windoku,
+    /// Cells a knight's move apart in chess must not contain the same digit, as in anti-knight sudoku.
+    AntiKnight,
+}
+
+/// A symmetry of the pattern of clues of a generated puzzle, see `Grid::generate_symmetric`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Symmetry {
+    /// Clues are placed without regard for symmetry.
+    None,
+    /// The pattern of clues is unchanged by a half turn of the grid.
+    Rotational180,
+    /// The pattern of clues is mirrored about the leading diagonal, from the top left to the bottom right.
+    Diagonal,
+}
+
+/// A logical technique used by the solver to set cells and eliminate candidates, see `SolverConfig`.
+/// 
+/// Eliminating the digits of set cells from their peers, and setting cells with a single candidate remaining,
+/// are fundamental to the solver and always take place.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Technique {
+    /// Set digits with only one possible cell in a row, column, or block.
+    HiddenSingles,
+    /// Eliminate the digits of two cells of a unit that can only hold the same two digits.
+    NakedPairs,
+    /// Eliminate digits confined to the intersection of a block and a row or column from the rest of either.
+    PointingPairs,
+    /// Eliminate the digits of three cells of a unit that can only hold the same three digits.
+    NakedTriples,
+    /// Eliminate the other digits of two cells that are the only cells of a unit where two digits are possible.
+    HiddenPairs,
+    /// Eliminate the other digits of three cells that are the only cells of a unit where three digits are possible.
+    HiddenTriples,
+    /// Eliminate digits using rectangles formed between two rows or columns.
+    XWing,
+    /// Eliminate the digits of four cells of a unit that can only hold the same four digits.
+    NakedQuads,
+}
+
+impl Technique {
+    /// Every technique, in the order the solver tries them.
+    const ALL: [Technique; 8] = [
+        Technique::HiddenSingles, Technique::NakedPairs, Technique::PointingPairs, Technique::NakedTriples,
+        Technique::HiddenPairs, Technique::HiddenTriples, Technique::XWing, Technique::NakedQuads,
+    ];
+}
+
+/// Configures which techniques the solver uses, and whether it may guess, see `Grid::solve_with`.
+/// 
+/// The default configuration enables everything, solving as `Grid::solve` does. Configurations are built up
+/// from it, such as `SolverConfig::new().technique(Technique::XWing, false).guessing(false)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SolverConfig {
+    /// Bitmap of the enabled techniques, indexed by `Technique` discriminants.
+    techniques: u16,
+    /// Whether the solver may guess once the techniques make no further progress.
+    guessing: bool,
+    /// The number of steps the search may take before giving up, see `Grid::solve_bounded`.
+    max_steps: usize,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self { techniques: u16::MAX, guessing: true, max_steps: usize::MAX }
+    }
+}
+
+impl SolverConfig {
+    /// Returns the default configuration, with every technique and guessing enabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Returns the configuration with no techniques enabled, such that only fundamental eliminations are made.
+    pub fn without_techniques(mut self) -> Self {
+        self.techniques = 0;
+        self
+    }
+    /// Returns the configuration with `technique` enabled or disabled.
+    pub fn technique(mut self, technique: Technique, enabled: bool) -> Self {
+        if enabled {
+            self.techniques |= 1 << technique as u16;
+        } else {
+            self.techniques &= !(1 << technique as u16);
+        }
+        self
+    }
+    /// Returns the configuration with guessing allowed or disallowed.
+    /// 
+    /// Without guessing, grids that the enabled techniques cannot complete fail with `SolveError::Stuck`.
+    pub fn guessing(mut self, enabled: bool) -> Self {
+        self.guessing = enabled;
+        self
+    }
+
+    /// Whether `technique` is enabled.
+    pub fn uses(&self, technique: Technique) -> bool {
+        self.techniques & 1 << technique as u16 != 0
+    }
+    /// Whether guessing is allowed.
+    pub fn allows_guessing(&self) -> bool {
+        self.guessing
+    }
+}
+
+/// Counts of the work done by the solver, see `Grid::solve_with_stats`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SolveStats {
+    /// Digits guessed, including each alternative tried after a failed guess.
+    pub guesses: usize,
+    /// Contradictions reached after guessing, each requiring a guess to be undone.
+    pub backtracks: usize,
+    /// Cells set as the only cell in a row, column, or block where a digit is possible.
+    pub hidden_singles: usize,
+    /// Candidates eliminated from cells, excluding those cleared by setting the cell.
+    pub eliminations: usize,
+}
+
+/// An event observed while solving, see `Grid::solve_with_observer`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StepEvent {
+    /// `digit` was set at `(x, y)`.
+    Set { digit: u8, x: usize, y: usize },
+    /// `digit` was eliminated as a candidate of `(x, y)`.
+    Eliminate { digit: u8, x: usize, y: usize },
+    /// `digit` was guessed at `(x, y)`, to be undone should it lead to a contradiction.
+    Guess { digit: u8, x: usize, y: usize },
+    /// A contradiction was reached on `digit` at `(x, y)`, undoing the most recent guess.
+    Backtrack { digit: u8, x: usize, y: usize },
+}
+
+#[cfg(feature = "serde")]
+impl<const BOX: usize, const N: usize, const BOX_HEIGHT: usize> serde::Serialize for GenericGrid<BOX, N, BOX_HEIGHT> {
+    /// Serializes the grid as its string of a digit per cell, see `Grid::to_str`.
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const BOX: usize, const N: usize, const BOX_HEIGHT: usize> serde::Deserialize<'de> for GenericGrid<BOX, N, BOX_HEIGHT> {
+    /// Deserializes the grid from its string of a digit per cell, see `Grid::try_from_str`.
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct GridVisitor<const BOX: usize, const N: usize, const BOX_HEIGHT: usize>;
+
+        impl<const BOX: usize, const N: usize, const BOX_HEIGHT: usize> serde::de::Visitor<'_> for GridVisitor<BOX, N, BOX_HEIGHT> {
+            type Value = GenericGrid<BOX, N, BOX_HEIGHT>;
 
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                write!(f, "a {}-character string of digits from 0 to {}", N * N, N)
+            }
+            fn visit_str<E: serde::de::Error>(self, str: &str) -> Result<GenericGrid<BOX, N, BOX_HEIGHT>, E> {
+                GenericGrid::try_from_str(str).map_err(E::custom)
+            }
+        }
+
+        deserializer.deserialize_str(GridVisitor)
+    }
+}
+
+/// Rating of how difficult a puzzle is to solve, by the hardest technique required.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Difficulty {
+    /// Solvable by eliminating candidates and setting cells with only one candidate remaining.
+    Trivial,
+    /// Requires hidden singles: digits with only one possible cell in a row, column, or block.
+    Easy,
+    /// Requires intermediate logical techniques, such as naked pairs or pointing pairs.
+    Medium,
+    /// Requires advanced logical techniques, such as naked quads or X-Wings.
+    Hard,
+    /// Cannot be solved by logic alone, guessing is required.
+    Guessing,
+}
+
+/// The kind of a unit, a group of cells that must each hold a different digit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnitKind {
+    Row,
+    Col,
+    /// A block, or a region of a grid with irregular regions, see `Grid::with_regions`.
+    Block,
+    /// One of the two main diagonals, as added by `Constraint::Diagonals`.
+    Diagonal,
+    /// One of the extra blocks added by `Constraint::Windoku`.
+    Window,
+}
+
+/// A single deduction made by the solver, see `Grid::next_step`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Step {
+    /// `digit` is set at `(x, y)`, as it is a given or was otherwise left pending by the solver.
+    Placement { digit: u8, x: usize, y: usize },
+    /// `digit` is set at `(x, y)`, as it is the only digit remaining possible there.
+    NakedSingle { digit: u8, x: usize, y: usize },
+    /// `digit` is set at `(x, y)`, as it is the only cell in a unit of the given kind where the digit is possible.
+    HiddenSingle { digit: u8, x: usize, y: usize, unit: UnitKind },
+    /// `digit` is no longer possible at `(x, y)`, as a cell in the same row, column, or block is set to it.
+    Elimination { digit: u8, x: usize, y: usize },
+    /// `digit` is no longer possible at `(x, y)`, as ruled out by a technique, see `Grid::explain`.
+    TechniqueElimination { digit: u8, x: usize, y: usize, technique: Technique },
+    /// `digit` is no longer possible at `(x, y)`, as ruled out by a killer cage or thermometer, see `Grid::explain`.
+    ConstraintElimination { digit: u8, x: usize, y: usize },
+}
+
+impl Step {
+    /// Returns a human-readable explanation of why the step may be taken.
+    pub fn reason(&self) -> &'static str {
+        match self {
+            Step::Placement { .. } => "the digit is given or was already deduced for this cell",
+            Step::NakedSingle { .. } => "the digit is the only one possible in this cell",
+            Step::HiddenSingle { unit: UnitKind::Row, .. } => "this is the only cell in its row where the digit is possible",
+            Step::HiddenSingle { unit: UnitKind::Col, .. } => "this is the only cell in its column where the digit is possible",
+            Step::HiddenSingle { unit: UnitKind::Block, .. } => "this is the only cell in its block where the digit is possible",
+            Step::HiddenSingle { unit: UnitKind::Diagonal, .. } => "this is the only cell on its diagonal where the digit is possible",
+            Step::HiddenSingle { unit: UnitKind::Window, .. } => "this is the only cell in its window where the digit is possible",
+            Step::Elimination { .. } => "the digit is already set elsewhere in this row, column, or block",
+            Step::TechniqueElimination { technique, .. } => match technique {
+                Technique::HiddenSingles => "another digit is the only one possible in this cell within one of its units",
+                Technique::NakedPairs => "two other cells of a unit can only hold the same two digits, including this one",
+                Technique::PointingPairs => "the digit is confined to where this row or column meets a block elsewhere",
+                Technique::NakedTriples => "three other cells of a unit can only hold the same three digits, including this one",
+                Technique::HiddenPairs => "two digits are only possible in this cell and one other of a unit",
+                Technique::HiddenTriples => "three digits are only possible in this cell and two others of a unit",
+                Technique::XWing => "the digit is confined to a rectangle of two rows or columns that crosses this cell",
+                Technique::NakedQuads => "four other cells of a unit can only hold the same four digits, including this one",
+            },
+            Step::ConstraintElimination { .. } => "the digit cannot complete a killer cage or thermometer through this cell",
+        }
+    }
+}
 
+/// A standard sudoku grid of nine by nine cells, divided into three by three blocks.
+pub type Grid = GenericGrid<3, 9>;
+/// A four by four sudoku grid, divided into two by two blocks.
+pub type Grid4 = GenericGrid<2, 4>;
+/// A sixteen by sixteen sudoku grid, divided into four by four blocks.
+pub type Grid16 = GenericGrid<4, 16>;
+/// A six by six sudoku grid, divided into blocks three cells wide and two cells tall.
+pub type Grid6 = GenericGrid<3, 6, 2>;
+/// An eight by eight sudoku grid, divided into blocks four cells wide and two cells tall.
+pub type Grid8 = GenericGrid<4, 8, 2>;
+/// A twelve by twelve sudoku grid, divided into blocks four cells wide and three cells tall.
+pub type Grid12 = GenericGrid<4, 12, 3>;
+
+/// A sudoku grid of `N` by `N` cells, divided into blocks `BOX` cells wide and `BOX_HEIGHT` cells tall,
+/// where `N` is `BOX` times `BOX_HEIGHT`. Blocks are square unless `BOX_HEIGHT` is given.
+/// 
+/// Digits range from 1 to `N`, as such `N` may be at most 16. Digits above 9 are written as letters from `A`,
+/// such that a sixteen by sixteen grid uses the digits 1 to 9 followed by `A` to `G`.
+/// 
+/// Grids own all of their state as plain data, without interior mutability or shared references, so are `Send`
+/// and `Sync`. They may be moved to other threads to be solved, or shared between threads to be read, while solving
+/// requires exclusive access through `&mut`. To solve a grid from several threads at once, clone it for each.
 #[derive(Debug, Clone)]
-pub struct Grid {
+pub struct GenericGrid<const BOX: usize, const N: usize, const BOX_HEIGHT: usize = BOX> {
     /// Digit of each cell, zero if blank.
-    cells: [[u8; 9]; 9],
+    cells: [[u8; N]; N],
     /// Bitmaps of possible digits per cell.
-    maybe: [[u16; 9]; 9],
+    maybe: [[u16; N]; N],
+    /// Whether each cell was given by the puzzle, rather than being deduced by the solver.
+    givens: [[bool; N]; N],
     
     /// Digit counts per row, indexed by rows then by digits.
-    row_digit_counters: [[u8; 9]; 9],
+    row_digit_counters: [[u8; N]; N],
     /// Digit counts per columns, indexed by columns then by digits.
-    col_digit_counters: [[u8; 9]; 9],
+    col_digit_counters: [[u8; N]; N],
     /// Digit counts per blocks, indexed by blocks then by digits.
-    blk_digit_counters: [[u8; 9]; 9],
+    blk_digit_counters: [[u8; N]; N],
+    /// Bitmaps of the cells where each digit is possible per row, indexed by rows then by digits, a bit per column.
+    row_digit_cells: [[u16; N]; N],
+    /// Bitmaps of the cells where each digit is possible per column, indexed likewise, a bit per row.
+    col_digit_cells: [[u16; N]; N],
+    /// Bitmaps of the cells where each digit is possible per block, indexed likewise, a bit per cell of `block_cells`.
+    blk_digit_cells: [[u16; N]; N],
+    /// Block of each cell, the `BOX` by `BOX_HEIGHT` blocks unless irregular regions are given, see `with_regions`.
+    blocks: [[u8; N]; N],
+    /// Indices of the cells of each block in row-major order, as given by `blocks`.
+    block_cells: [[(usize, usize); N]; N],
+    /// Position of each cell within the `block_cells` of its block.
+    block_positions: [[u8; N]; N],
+
+    /// Whether the main diagonals must also contain each digit once, see `Constraint::Diagonals`.
+    diagonals: bool,
+    /// Whether the windoku windows must also contain each digit once, see `Constraint::Windoku`.
+    windoku: bool,
+    /// Cells of the units added by constraints beyond the rows, columns, and blocks: the leading diagonal
+    /// and anti-diagonal if `diagonals` is set, followed by the windows if `windoku` is set.
+    extra_units: List<[(usize, usize); N], N, 2>,
+    /// Bitmap of the extra units each cell is part of.
+    extra_units_of: [[u16; N]; N],
+    /// Digit counts per extra unit, indexed by units then by digits.
+    extra_digit_counters: List<[u8; N], N, 2>,
+    /// Whether cells a knight's move apart must differ, see `Constraint::AntiKnight`.
+    anti_knight: bool,
+
+    /// Killer cages whose digits must differ and add up to their sums, see `Grid::add_cage`.
+    cages: List<Cage<N>, N, N>,
+    /// Index of the cage of each cell plus one, zero if the cell is not in a cage.
+    cage_of: [[u16; N]; N],
+    /// Thermometers along which digits must strictly increase, see `Grid::add_thermo`.
+    thermos: List<Thermo<N>, N, N>,
     
     /// Stack of 'maybe' deletions to take to take.
-    del_maybes: Vec<(usize, (usize, usize))>,
+    del_maybes: OpStack<N>,
     /// Stack of cell sets to take to take.
-    set_cells: Vec<(usize, (usize, usize))>,
+    set_cells: OpStack<N>,
+
+    /// Stack of changes made to the grid, such that they can be undone when a guess fails.
+    trail: Trail<N>,
 }
 
-impl Default for Grid {
-    /// Returns an empty grid.
-    fn default() -> Self {
-        Self {
-            cells: [[0u8; 9]; 9],
-            maybe: [[Grid::MAYBE_ALL; 9]; 9],
+/// Iterator over the solutions of a grid, see `Grid::solutions`.
+#[derive(Debug, Clone)]
+pub struct Solutions<const BOX: usize = 3, const N: usize = 9, const BOX_HEIGHT: usize = BOX> {
+    /// Grid being searched, in the state of the most recent guess.
+    grid: GenericGrid<BOX, N, BOX_HEIGHT>,
+    /// Stack of guesses leading to the grid's current state.
+    guesses: Guesses<N>,
+    /// Whether all possibilities have been searched.
+    exhausted: bool,
+    /// The most recent contradiction reached by the search, if any.
+    contradiction: Option<SolveError>,
+}
 
-            row_digit_counters: [[9u8; 9]; 9],
-            col_digit_counters: [[9u8; 9]; 9],
-            blk_digit_counters: [[9u8; 9]; 9],
+impl<const BOX: usize, const N: usize, const BOX_HEIGHT: usize> Iterator for Solutions<BOX, N, BOX_HEIGHT> {
+    type Item = GenericGrid<BOX, N, BOX_HEIGHT>;
 
-            del_maybes: Vec::new(),
-            set_cells: Vec::new(),
+    fn next(&mut self) -> Option<GenericGrid<BOX, N, BOX_HEIGHT>> {
+        while !self.exhausted {
+            if let Err(error) = self.grid.propagate() {
+                // A contradiction was reached, backtrack to explore the other possibilities.
+                self.contradiction = Some(error);
+                self.exhausted = !self.grid.next_guess(&mut self.guesses);
+                continue;
+            }
+
+            if self.grid.maybe != [[0; N]; N] {
+                // Each possibility of the cell is mutually exclusive, so their solutions are distinct.
+                let index = self.grid.choose_guess_cell().unwrap();
+                self.guesses.push((self.grid.trail.len(), index, self.grid.maybe[index.0][index.1]));
+                self.grid.next_guess(&mut self.guesses);
+                continue;
+            }
+
+            let mut solution = self.grid.clone();
+            solution.trail.clear();
+            self.exhausted = !self.grid.next_guess(&mut self.guesses);
+            return Some(solution);
         }
+
+        None
     }
 }
 
-impl Grid {
-    const MAYBE_ALL: u16 = 0x1ff;
+impl<const BOX: usize, const N: usize, const BOX_HEIGHT: usize> core::iter::FusedIterator for Solutions<BOX, N, BOX_HEIGHT> {}
 
-    /// Parses an 81-character string of ASCII digits from 0 to 9, returning a Grid on success.
-    pub fn from_str(str: String) -> Self {
-        let str = str.trim();
-        assert_eq!(str.len(), 81, "Grid string not 81 characters long, instead {}.", str.len());
+/// Builder of an empty grid combining any number of constraints, see `Grid::builder`.
+/// 
+/// Every constraint added contributes to the solver's eliminations and to verifying solutions.
+#[derive(Debug, Clone)]
+pub struct GridBuilder<const BOX: usize = 3, const N: usize = 9, const BOX_HEIGHT: usize = BOX> {
+    grid: GenericGrid<BOX, N, BOX_HEIGHT>,
+}
 
-        let mut grid = Grid::default();
+impl<const BOX: usize, const N: usize, const BOX_HEIGHT: usize> GridBuilder<BOX, N, BOX_HEIGHT> {
+    /// Add a constraint, as per `Grid::with_constraint`.
+    pub fn constraint(mut self, constraint: Constraint) -> Self {
+        self.grid = self.grid.with_constraint(constraint);
+        self
+    }
+    /// Add `Constraint::Diagonals`.
+    pub fn diagonals(self) -> Self {
+        self.constraint(Constraint::Diagonals)
+    }
+    /// Add `Constraint::Windoku`.
+    pub fn windoku(self) -> Self {
+        self.constraint(Constraint::Windoku)
+    }
+    /// Add `Constraint::AntiKnight`.
+    pub fn anti_knight(self) -> Self {
+        self.constraint(Constraint::AntiKnight)
+    }
+    /// Replace the blocks with irregular regions, as per `Grid::with_regions`.
+    pub fn regions(mut self, regions: [[u8; N]; N]) -> Self {
+        self.grid = self.grid.with_regions(regions);
+        self
+    }
+    /// Returns the empty grid with every constraint added, into which puzzles may be loaded with `load_str`.
+    pub fn build(self) -> GenericGrid<BOX, N, BOX_HEIGHT> {
+        self.grid
+    }
+}
 
-        for (i, char) in str.chars().enumerate() {
-            let digit = char.to_digit(10).expect("Unexpected character in grid string.");
-            if digit != 0 {
-                let y = i / 9;
-                let x = i - y * 9;
-                
-                grid.set_cells.push((digit as usize, (x, y)))
-            }
-        }
+/// A guess made by the solver, as the trail length prior to the guess, the cell guessed,
+/// and the bitmap of the possibilities yet to be tried.
+type Guess = (usize, (usize, usize), u16);
+/// Stack of guesses, of which there are at most one per cell.
+type Guesses<const N: usize> = Stack<Guess, N, N, 1>;
+/// Stack of changes, of which there are at most one per possibility and one per cell, within `17 * N * N`.
+type Trail<const N: usize> = Stack<Change, N, N, 17>;
 
-        grid
+/// A killer cage, a group of cells whose digits must differ and add up to a sum, see `Grid::add_cage`.
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "alloc"), derive(Copy))]
+struct Cage<const N: usize> {
+    /// Indices of the cells of the cage.
+    cells: List<(usize, usize), N, 1>,
+    /// The sum the digits of the cells must add up to.
+    sum: usize,
+}
+
+impl<const N: usize> Cage<N> {
+    /// Iterate over the bitmaps of each set of distinct digits adding up to the sum, with a digit per cell.
+    fn combos(&self) -> impl Iterator<Item = u16> {
+        let sum = self.sum;
+        // Step through the sets of as many digits as there are cells in increasing order, by Gosper's hack.
+        let mut combo = (1u32 << self.cells.len()) - 1;
+        core::iter::from_fn(move || {
+            while combo < 1 << N {
+                let current = combo;
+                let lowest = combo & combo.wrapping_neg();
+                let ripple = combo + lowest;
+                combo = ripple | (((combo ^ ripple) >> 2) / lowest);
+
+                if (0..N).filter(|di| current & 1 << di != 0).map(|di| di + 1).sum::<usize>() == sum {
+                    return Some(current as u16);
+                }
+            }
+            None
+        })
     }
-    /// Serialize grid into an 81-character string of ASCII digits from 0 to 9.
-    pub fn to_str(&self) -> String {
-        let mut str = String::with_capacity(81);
+    /// Check whether the digits of the cells differ and add up to the sum, given a board without blank cells.
+    fn is_complete(&self, board: &[[u8; N]; N]) -> bool {
+        let seen = self.cells.iter().fold(0u16, |seen, &(x, y)| seen | 1 << (board[x][y] - 1));
+        seen.count_ones() as usize == self.cells.len()
+            && self.cells.iter().map(|&(x, y)| board[x][y] as usize).sum::<usize>() == self.sum
+    }
+}
 
-        for y in 0..9 {
-            for x in 0..9 {
-                str.push(char::from_digit(self.cells[x][y] as u32, 10)
-                    .expect("grid contains invalid cell?"));
+/// A thermometer, a path of cells along which digits must strictly increase from the bulb, see `Grid::add_thermo`.
+#[derive(Debug, Clone)]
+#[cfg_attr(not(feature = "alloc"), derive(Copy))]
+struct Thermo<const N: usize> {
+    /// Indices of the cells of the thermometer, starting from the bulb.
+    cells: List<(usize, usize), N, 1>,
+}
+
+impl<const N: usize> Thermo<N> {
+    /// Check whether the digits along the thermometer increase, ignoring blank cells apart from requiring
+    /// enough digits to remain around the set cells to fill them.
+    fn fits(&self, board: &[[u8; N]; N]) -> bool {
+        // The lowest digit the cell may hold, given the digits before it.
+        let mut floor = 1;
+        for (i, &(x, y)) in self.cells.iter().enumerate() {
+            let digit = board[x][y] as usize;
+            if digit != 0 {
+                if digit < floor || digit + (self.cells.len() - 1 - i) > N {
+                    return false;
+                }
+                floor = digit;
             }
+            floor += 1;
         }
-
-        str
+        true
     }
+}
 
-    /// Check whether the grid is in a valid solved state or not.
-    pub fn verify_solution(&self) -> bool {
+/// A change made to the grid's state, recorded such that it can be reverted.
+#[derive(Debug, Clone, Copy)]
+enum Change {
+    /// The cell at the index was set.
+    SetCell((usize, usize)),
+    /// The digit was removed from the maybes of the cell at the index.
+    DelMaybe(usize, (usize, usize)),
+}
 
-        // Check for exhaustion of maybes/available blocks
-        if self.maybe              != [[0u16; 9]; 9]
-        || self.row_digit_counters != [[0u8; 9]; 9]
-        || self.col_digit_counters != [[0u8; 9]; 9] 
-        || self.blk_digit_counters != [[0u8; 9]; 9] {
-            return false;
-        }
+impl<const BOX: usize, const N: usize, const BOX_HEIGHT: usize> Default for GenericGrid<BOX, N, BOX_HEIGHT> {
+    /// Returns an empty grid.
+    fn default() -> Self {
+        let mut grid = Self {
+            cells: [[0u8; N]; N],
+            maybe: [[Self::MAYBE_ALL; N]; N],
+            givens: [[false; N]; N],
 
-        // Ensure there is one of each digit in every row, column, and block
-        let mut row_digit_counters = [[0u8; 9]; 9];
-        let mut col_digit_counters = [[0u8; 9]; 9];
-        let mut blk_digit_counters = [[0u8; 9]; 9];
-        
-        for y in 0..9 {
-            for x in 0..9 {
-                if self.cells[x][y] == 0 { return false; }
+            row_digit_counters: [[N as u8; N]; N],
+            col_digit_counters: [[N as u8; N]; N],
+            blk_digit_counters: [[N as u8; N]; N],
+            row_digit_cells: [[Self::MAYBE_ALL; N]; N],
+            col_digit_cells: [[Self::MAYBE_ALL; N]; N],
+            blk_digit_cells: [[Self::MAYBE_ALL; N]; N],
+            blocks: core::array::from_fn(|x| core::array::from_fn(|y| (x / BOX + y / BOX_HEIGHT * (N / BOX)) as u8)),
+            block_cells: [[(0, 0); N]; N],
+            block_positions: [[0; N]; N],
 
-                row_digit_counters[y                ][self.cells[x][y] as usize - 1] += 1;
-                col_digit_counters[x                ][self.cells[x][y] as usize - 1] += 1;
-                blk_digit_counters[x / 3 + y / 3 * 3][self.cells[x][y] as usize - 1] += 1;
-            }
-        }
+            diagonals: false,
+            windoku: false,
+            extra_units: List::new(),
+            extra_units_of: [[0u16; N]; N],
+            extra_digit_counters: List::new(),
+            anti_knight: false,
+
+            cages: List::new(),
+            cage_of: [[0u16; N]; N],
+            thermos: List::new(),
 
-        row_digit_counters == [[1u8; 9]; 9] &&
-        col_digit_counters == [[1u8; 9]; 9] &&
-        blk_digit_counters == [[1u8; 9]; 9]
+            del_maybes: OpStack::new(),
+            set_cells: OpStack::new(),
+
+            trail: Trail::new(),
+        };
+        grid.index_blocks();
+        grid
     }
+}
 
-    /// Attempt to solve the grid, returning `Ok(())` on success and `Err(())` on failure.
-    /// 
-    /// `Err(())` leaves the grid in an undefined state, however `verify_solution` will still give an accurate result.
-    /// 
-    /// It may be desired to call `verify_solution` on the grid hereafter, however this shouldn't be necessary.
-    pub fn solve(&mut self) -> Result<(), ()> {
-        loop {
-            loop { // Begin solving through elimination and hidden singles in a loop.
-                if let Some((digit, index)) = self.del_maybes.pop() {
-                    self.del_maybe(digit, index)?;
-                } else if let Some((digit, index)) = self.set_cells.pop() {
-                    self.set_cell(digit, index)?;
-                } else {
-                    // Attempt to detect any cells where it is the only possible option of a row/column/block,
-                    // even if it itself has multiple possibilities.
-                    self.find_hidden_singles();
+impl<const BOX: usize, const N: usize, const BOX_HEIGHT: usize> PartialEq for GenericGrid<BOX, N, BOX_HEIGHT> {
+    /// Compares the digits of the grids, not their candidates, givens, or constraints.
+    fn eq(&self, other: &Self) -> bool {
+        self.board() == other.board()
+    }
+}
 
-                    if self.set_cells.len() == 0 {
-                        // Solver has exhausted its capabilities
-                        break;
-                    }
-                }
-            }
+impl<const BOX: usize, const N: usize, const BOX_HEIGHT: usize> Eq for GenericGrid<BOX, N, BOX_HEIGHT> {}
 
-            if self.maybe == [[0; 9]; 9] {
-                // Grid has been solved, return
-                return Ok(());
-            } else {
-                // Make a binary guess and use process of elimination to pick the correct one (binary tree nav style)
-                let (pair_digit, pair_indecies) = self.find_maybe_pair();
+impl<const BOX: usize, const N: usize, const BOX_HEIGHT: usize> core::hash::Hash for GenericGrid<BOX, N, BOX_HEIGHT> {
+    /// Hashes the digits of the grid, consistently with `PartialEq`.
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.board().hash(state);
+    }
+}
 
-                let mut hypothetical = self.clone();
-                hypothetical.set_cells.push((pair_digit, pair_indecies[0]));
+impl<const BOX: usize, const N: usize, const BOX_HEIGHT: usize> fmt::Display for GenericGrid<BOX, N, BOX_HEIGHT> {
+    /// Formats the grid as rows of digits separated into blocks, with blanks shown as `.`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let board = self.board();
+        let border = |f: &mut fmt::Formatter<'_>| {
+            for _ in 0..N / BOX {
+                f.write_str("+")?;
+                for _ in 0..BOX {
+                    f.write_str("-")?;
+                }
+            }
+            f.write_str("+")
+        };
 
-                if let Err(_) = hypothetical.solve() {
-                    // Hypothetical guess failed, thus the other of the binary possibility must be correct.
-                    self.set_cells.push((pair_digit, pair_indecies[1]));
-                    continue;
-                } else {
-                    // Guess was correct and a solution was found, return.
-                    *self = hypothetical;
-                    return Ok(());
+        for y in 0..N {
+            if y % BOX_HEIGHT == 0 {
+                border(f)?;
+                f.write_str("\n")?;
+            }
+            for x in 0..N {
+                if x % BOX == 0 {
+                    f.write_str("|")?;
                 }
+                write!(f, "{}", Self::digit_char(board[x][y], '.'))?;
             }
+            f.write_str("|\n")?;
         }
+        border(f)
     }
+}
 
-    fn set_cell(&mut self, digit: usize, index: (usize, usize)) -> Result<(), ()> {
-        // Repeat check: check if already set
-        if self.cells[index.0][index.1] != 0 {
-            return if self.cells[index.0][index.1] != digit as u8 {
-                Err(()) // Already set to something different, this is a contradiction.
-            } else {
-                Ok(()) // Already set to the correct value, no action necessary.
-            };
-        }
+impl<const BOX: usize, const N: usize, const BOX_HEIGHT: usize> core::str::FromStr for GenericGrid<BOX, N, BOX_HEIGHT> {
+    type Err = ParseError;
 
-        // Contradiction check: if attempt to set a cell that is not maybe the digit, return Err
-        if self.maybe[index.0][index.1] & 1 << digit - 1 == 0 {
-            return Err(());
-        }
+    /// Parses either a grid string of a character per cell, see `Grid::try_from_str`,
+    /// or a multi-line layout such as that produced by `Display`, see `Grid::from_grid_str`.
+    fn from_str(str: &str) -> Result<Self, ParseError> {
+        Self::try_from_str(str).or_else(|err| Self::from_grid_str(str).map_err(|_| err))
+    }
+}
 
+impl<const BOX: usize, const N: usize, const BOX_HEIGHT: usize> GenericGrid<BOX, N, BOX_HEIGHT> {
+    const MAYBE_ALL: u16 = {
+        assert!(BOX * BOX_HEIGHT == N && N <= 16, "grid size must be the area of the blocks, and at most 16");
+        ((1u32 << N) - 1) as u16
+    };
 
-        for x in 0..9 { // Remove maybes for each cell in row
-            if self.maybe[x][index.1] & 1 << digit - 1 != 0 {
-                self.del_maybes.push((digit, (x, index.1)));
-            }
+    /// Parses a string of `N * N` characters, a cell each in row-major order, returning a Grid on success.
+    /// Digits are given from `1` to `9` followed by letters from `A`, and blank cells as `0`, such that a standard
+    /// grid takes 81 ASCII digits from 0 to 9.
+    /// 
+    /// Blank cells may also be given as `.`, `_`, or a space.
+    /// Panics if the string is invalid; see `try_from_str` or `str::parse` for non-panicking alternatives.
+    #[allow(clippy::should_implement_trait)]
+    #[cfg(feature = "alloc")]
+    pub fn from_str(str: String) -> Self {
+        Self::try_from_str(&str).unwrap_or_else(|err| panic!("{}.", err))
+    }
+    /// Parses a string of `N * N` characters as `from_str` does, returning an error if the string is invalid.
+    /// 
+    /// Blank cells may also be given as `.`, `_`, or a space.
+    pub fn try_from_str(str: &str) -> Result<Self, ParseError> {
+        Self::parse(str, Self::is_common_blank)
+    }
+    /// Parses a string of `N * N` characters as `try_from_str` does, with blank cells given as either `0` or `blank`.
+    pub fn from_str_with_blank(str: &str, blank: char) -> Result<Self, ParseError> {
+        Self::parse(str, |char| char == blank)
+    }
+    /// Parses a grid laid out over multiple lines, such as that produced by `Display`.
+    /// 
+    /// All whitespace and `|`, `-`, and `+` separators are ignored, after which exactly `N * N` digits must remain,
+    /// as accepted by `try_from_str`. Blank cells may also be given as `.` or `_`.
+    pub fn from_grid_str(str: &str) -> Result<Self, ParseError> {
+        let mut grid = Self::default();
+        grid.parse_cells(Self::grid_cells(str.chars()), Self::is_common_blank)?;
+        Ok(grid)
+    }
+    /// Parses a grid in the `.sdk` or `.ss` formats most puzzle collections are distributed in.
+    /// 
+    /// Comment lines starting with `#` and metadata lines starting with `[`, such as `[Puzzle]`, are skipped, after
+    /// which the remaining lines are read as per `from_grid_str`, such that the separators of `.ss` files are ignored.
+    pub fn from_sdk(str: &str) -> Result<Self, ParseError> {
+        let body = str.lines()
+            .filter(|line| !matches!(line.trim_start().chars().next(), Some('#' | '[')))
+            .flat_map(str::chars);
+
+        let mut grid = Self::default();
+        grid.parse_cells(Self::grid_cells(body), Self::is_common_blank)?;
+        Ok(grid)
+    }
+    fn grid_cells(chars: impl Iterator<Item = char> + Clone) -> impl Iterator<Item = char> + Clone {
+        //! Skip the whitespace and separators of a multi-line layout, see `from_grid_str`.
+
+        chars.filter(|&char| !char.is_whitespace() && !matches!(char, '|' | '-' | '+'))
+    }
+    fn is_common_blank(char: char) -> bool {
+        matches!(char, '.' | '_' | ' ')
+    }
+    fn parse(str: &str, is_blank: impl Fn(char) -> bool) -> Result<Self, ParseError> {
+        let mut grid = Self::default();
+        grid.parse_into(str, is_blank)?;
+        Ok(grid)
+    }
+    fn parse_into(&mut self, str: &str, is_blank: impl Fn(char) -> bool) -> Result<(), ParseError> {
+        //! Parse the grid string into an empty grid.
+
+        // Surrounding spaces may be blanks, so are only discarded if there are otherwise too many characters.
+        let mut str = str.trim_matches(|char: char| char.is_whitespace() && char != ' ');
+        if str.chars().count() != N * N {
+            str = str.trim();
         }
-        for y in 0..9 { // Remove maybes for each cell in column
-            if self.maybe[index.0][y] & 1 << digit - 1 != 0 {
-                self.del_maybes.push((digit, (index.0, y)));
-            }
+
+        self.parse_cells(str.chars(), is_blank)
+    }
+    fn parse_cells(&mut self, chars: impl Iterator<Item = char> + Clone, is_blank: impl Fn(char) -> bool) -> Result<(), ParseError> {
+        //! Parse exactly `N * N` characters, one per cell in row-major order, into an empty grid.
+
+        let len = chars.clone().count();
+        if len != N * N {
+            return Err(ParseError::InvalidLength(len));
         }
 
-        let blk_x = index.0 / 3 * 3;
-        let blk_y = index.1 / 3 * 3;
-        for y in blk_y..(blk_y + 3) { // Remove maybes for each cell in block
-            for x in blk_x..(blk_x + 3) {
-                if self.maybe[x][y] & 1 << digit - 1 != 0 {
-                    self.del_maybes.push((digit, (x, y)));
-                }
+        for (i, char) in chars.enumerate() {
+            let digit = if is_blank(char) {
+                0
+            } else {
+                char.to_digit(N as u32 + 1).ok_or(ParseError::InvalidCharacter(char))?
+            };
+            if digit != 0 {
+                let y = i / N;
+                let x = i - y * N;
+                
+                self.set_cells.push((digit as usize, (x, y)));
+                self.givens[x][y] = true;
             }
         }
 
-        // Set cell
-        self.cells[index.0][index.1] = digit as u8;
-
-        // Erase maybes
-        let mut maybes = self.maybe[index.0][index.1];
-        while maybes != 0 {
-            let di = maybes.trailing_zeros();
-            self.update_counters(di as usize + 1, index);
-            maybes ^= 1 << di;
-        }
-        self.maybe[index.0][index.1] = 0;
-        
         Ok(())
     }
-    fn del_maybe(&mut self, digit: usize, index: (usize, usize)) -> Result<(), ()> {
-        // If already unmaybed, return early
-        if self.maybe[index.0][index.1] & 1 << digit - 1 == 0 {
-            return Ok(());
-        }
 
-        // Delete maybe
-        self.maybe[index.0][index.1] &= !(1 << digit - 1);
+    /// Restore the grid to an empty state, keeping the capacity of its allocations for reuse.
+    /// 
+    /// Any constraints added by `with_constraint` are kept.
+    pub fn reset(&mut self) {
+        self.cells = [[0u8; N]; N];
+        self.maybe = [[Self::MAYBE_ALL; N]; N];
+        self.givens = [[false; N]; N];
 
-        // If there is only one remaining digit that may be set, set the cell.
-        if self.maybe[index.0][index.1].count_ones() == 1 {
-            self.set_cells.push((self.maybe[index.0][index.1].trailing_zeros() as usize + 1, index));
+        self.row_digit_counters = [[N as u8; N]; N];
+        self.col_digit_counters = [[N as u8; N]; N];
+        self.blk_digit_counters = [[N as u8; N]; N];
+        self.row_digit_cells = [[Self::MAYBE_ALL; N]; N];
+        self.col_digit_cells = [[Self::MAYBE_ALL; N]; N];
+        self.blk_digit_cells = [[Self::MAYBE_ALL; N]; N];
+        for counters in self.extra_digit_counters.iter_mut() {
+            *counters = [N as u8; N];
         }
 
-        self.update_counters(digit, index);
+        self.del_maybes.clear();
+        self.set_cells.clear();
 
-        Ok(())
+        self.trail.clear();
     }
-    fn update_counters(&mut self, digit: usize, index: (usize, usize)) {
-        //! Decrement the row, column, and block counters according to the digit.
-        
-        self.row_digit_counters[index.1                      ][digit - 1] -= 1;
-        self.col_digit_counters[index.0                      ][digit - 1] -= 1;
-        self.blk_digit_counters[index.0 / 3 + index.1 / 3 * 3][digit - 1] -= 1;// 5, 6   1 + 6 = 7
+    /// Reset the grid and parse a new grid string into it, as per `try_from_str`.
+    /// 
+    /// This avoids reallocating when solving many grids in turn, such that puzzles solved without guessing,
+    /// as reported by `SolveStats::guesses`, perform no heap allocations once the grid's stacks have grown to fit.
+    /// On error, the grid is left partially loaded.
+    pub fn load_str(&mut self, str: &str) -> Result<(), ParseError> {
+        self.reset();
+        self.parse_into(str, Self::is_common_blank)
     }
+    /// Constructs a grid from an array of digits from 0 to `N` indexed by `[x][y]`, zero if blank.
+    /// 
+    /// The resulting grid is identical to the one produced by `from_str` for the same digits.
+    pub fn from_cells(cells: [[u8; N]; N]) -> Result<Self, ParseError> {
+        let mut grid = Self::default();
 
-    fn find_hidden_singles(&mut self) {
-        for row in 0..9 {
-            for di in 0..9 {
-                if self.row_digit_counters[row][di] == 1 {
-                    // hidden single located, find and set
-                    for x in 0..9 {
-                        if self.maybe[x][row] & 1 << di != 0 {
-                            self.set_cells.push((di + 1, (x, row)));
-                        }
-                    }
+        for y in 0..N {
+            for x in 0..N {
+                let value = cells[x][y];
+                if value as usize > N {
+                    return Err(ParseError::InvalidDigit { x, y, value });
                 }
-            }
-        }
-        for col in 0..9 {
-            for di in 0..9 {
-                if self.col_digit_counters[col][di] == 1 {
-                    // hidden single located, find and set
-                    for y in 0..9 {
-                        if self.maybe[col][y] & 1 << di != 0 {
-                            self.set_cells.push((di + 1, (col, y)));
-                        }
-                    }
+                if value != 0 {
+                    grid.set_cells.push((value as usize, (x, y)));
+                    grid.givens[x][y] = true;
                 }
             }
         }
-        for blk in 0..9 {
-            for di in 0..9 {
-                if self.blk_digit_counters[blk][di] == 1 {
-                    // hidden single located, find and set
-                    let blk_y = blk / 3 * 3;
-                    let blk_x = (blk - blk_y) * 3;
 
-                    for y in blk_y..(blk_y + 3) {
-                        for x in blk_x..(blk_x + 3) {
-                            if self.maybe[x][y] & 1 << di != 0 {
-                                self.set_cells.push((di + 1, (x, y)));
-                            }
-                        }
-                    }
-                }
-            }
-        }
+        Ok(grid)
     }
-    fn find_maybe_pair(&self) -> (usize, [(usize, usize); 2]) {
-        //! Search the grid for a binary maybe and return the two possibilities as `(digit, [(x index, y index); 2])`.
-        // searches do not terminate early on counter == 2 such that they error if state is invalid
-
-        let mut cell_index = 0;
-        let mut cells = [(usize::MAX, usize::MAX); 2];
+    /// Constructs a grid as `from_cells` does, starting from the given candidates of each cell rather than every digit,
+    /// such as to resume from pencil marks. `candidates` is indexed by `[x][y]` with bit `n` set if digit `n + 1` is a
+    /// candidate, as returned by `candidates`.
+    /// 
+    /// The candidates of a blank cell must be non-empty, while those of a set cell must either be empty or include its
+    /// digit. Candidates that contradict the digits of the grid are eliminated as usual upon solving. Candidates are not
+    /// kept by `with_constraint`, `with_regions`, and `add_cage`, which recompute them.
+    pub fn with_candidates(cells: [[u8; N]; N], candidates: [[u16; N]; N]) -> Result<Self, ParseError> {
+        let mut grid = Self::from_cells(cells)?;
+        let all = (1u16 << N) - 1;
 
-        for row in 0..9 {
-            for di in 0..9 {
-                if self.row_digit_counters[row][di] == 2 {
-                    // pair of digit has been found in row; locate the cells
-                    for x in 0..9 {
-                        if self.maybe[x][row] & 1 << di != 0 {
-                            cells[cell_index] = (x, row);
-                            cell_index += 1;
-                        }
-                    }
-                    return (di + 1, cells);
+        for y in 0..N {
+            for x in 0..N {
+                let maybes = candidates[x][y];
+                let valid = match cells[x][y] {
+                    0 => maybes != 0,
+                    digit => maybes == 0 || maybes & 1 << (digit - 1) != 0,
+                };
+                if !valid || maybes & !all != 0 {
+                    return Err(ParseError::InvalidCandidates { x, y });
                 }
-            }
-        }
-        for col in 0..9 {
-            for di in 0..9 {
-                if self.col_digit_counters[col][di] == 2 {
-                    // pair of digit has been found in col; locate the cells
-                    for y in 0..9 {
-                        if self.maybe[col][y] & 1 << di != 0 {
-                            cells[cell_index] = (col, y);
-                            cell_index += 1;
-                        }
+                if cells[x][y] == 0 {
+                    for di in (0..N).filter(|&di| maybes & 1 << di == 0) {
+                        grid.del_maybes.push((di + 1, (x, y)));
                     }
-                    return (di + 1, cells);
                 }
             }
         }
-        for blk in 0..9 {
-            for di in 0..9 {
-                if self.blk_digit_counters[blk][di] == 2 {
-                    // pair of digit has been found in blk; locate the cells
-                    let blk_y = blk / 3 * 3;
-                    let blk_x = (blk - blk_y) * 3;
 
-                    for y in blk_y..(blk_y + 3) {
-                        for x in blk_x..(blk_x + 3) {
-                            if self.maybe[x][y] & 1 << di != 0 {
-                                cells[cell_index] = (x, y);
-                                cell_index += 1;
-                            }
-                        }
-                    }
-                    return (di + 1, cells);
-                }
-            }
+        Ok(grid)
+    }
+    /// Returns a builder of an empty grid, such that several constraints may be combined.
+    pub fn builder() -> GridBuilder<BOX, N, BOX_HEIGHT> {
+        GridBuilder { grid: Self::default() }
+    }
+    /// Returns the grid with an additional rule its digits must satisfy, alongside any added before.
+    pub fn with_constraint(mut self, constraint: Constraint) -> Self {
+        match constraint {
+            Constraint::Diagonals => self.diagonals = true,
+            Constraint::Windoku => self.windoku = true,
+            Constraint::AntiKnight => self.anti_knight = true,
         }
-
-        panic!("binary possiblity could not be found, this is likely an implementation error")
+        self.index_extra_units();
+        self.requeue_board();
+        self
     }
-}
 
-#[cfg(test)]
+    /// Returns the grid with its blocks replaced by irregular regions, as in jigsaw sudoku, where `regions` gives
+    /// the region from 0 to `N - 1` of each cell indexed by `[x][y]`.
+    /// 
+    /// `Display` still separates the cells into `BOX` by `BOX_HEIGHT` blocks regardless.
+    /// 
+    /// Panics if a region is out of range or does not hold exactly `N` cells.
+    pub fn with_regions(mut self, regions: [[u8; N]; N]) -> Self {
+        let mut sizes = [0usize; N];
+        for (x, column) in regions.iter().enumerate() {
+            for (y, &region) in column.iter().enumerate() {
+                assert!((region as usize) < N, "region {} of cell ({}, {}) out of range, must be within 0 to {}.", region, x, y, N - 1);
+                sizes[region as usize] += 1;
+            }
+        }
+        if let Some(region) = sizes.iter().position(|&size| size != N) {
+            panic!("region {} holds {} cells, must hold {}.", region, sizes[region], N);
+        }
+
+        self.blocks = regions;
+        self.index_blocks();
+        self.requeue_board();
+        self
+    }
+    fn index_extra_units(&mut self) {
+        //! Collect the cells of the units added by the constraints, see `extra_units`.
+
+        self.extra_units.clear();
+        if self.diagonals {
+            self.extra_units.push(core::array::from_fn(|i| (i, i)));
+            self.extra_units.push(core::array::from_fn(|i| (N - 1 - i, i)));
+        }
+        if self.windoku {
+            assert!(BOX.abs_diff(BOX_HEIGHT) <= 1, "windows do not fit between blocks of {} by {} cells.", BOX, BOX_HEIGHT);
+
+            // Windows are separated by a line of cells, starting a cell in from the edge.
+            for wy in 0..N / BOX_HEIGHT - 1 {
+                for wx in 0..N / BOX - 1 {
+                    let (ox, oy) = (1 + wx * (BOX + 1), 1 + wy * (BOX_HEIGHT + 1));
+                    self.extra_units.push(core::array::from_fn(|i| (ox + i % BOX, oy + i / BOX)));
+                }
+            }
+        }
+
+        self.extra_units_of = [[0; N]; N];
+        for (unit, cells) in self.extra_units.iter().enumerate() {
+            for &(x, y) in cells {
+                self.extra_units_of[x][y] |= 1 << unit;
+            }
+        }
+        self.extra_digit_counters.clear();
+        for _ in 0..self.extra_units.len() {
+            self.extra_digit_counters.push([N as u8; N]);
+        }
+    }
+    fn index_blocks(&mut self) {
+        //! Collect the cells of each block according to `blocks`.
+
+        let mut sizes = [0usize; N];
+        for y in 0..N {
+            for x in 0..N {
+                let blk = self.blocks[x][y] as usize;
+                self.block_cells[blk][sizes[blk]] = (x, y);
+                self.block_positions[x][y] = sizes[blk] as u8;
+                sizes[blk] += 1;
+            }
+        }
+    }
+
+    /// Add a killer cage, such that the digits of `cells` must differ and add up to `sum` while solving and verifying.
+    /// 
+    /// Candidates that cannot complete the sum are eliminated as the grid is solved. Cages that no digits
+    /// can complete, such as those summing to more than the largest digits, leave the grid without a solution.
+    /// 
+    /// Panics if `cells` is empty, holds more than `N` cells, or holds a cell out of bounds, more than once,
+    /// or that is already part of a cage.
+    pub fn add_cage(&mut self, cells: &[(usize, usize)], sum: u8) {
+        assert!(!cells.is_empty() && cells.len() <= N, "cage of {} cells, must hold within 1 to {}.", cells.len(), N);
+        for (i, &(x, y)) in cells.iter().enumerate() {
+            assert!(x < N && y < N, "cell index ({}, {}) out of bounds, must be within 0 to {}.", x, y, N - 1);
+            assert!(!cells[..i].contains(&(x, y)), "cell ({}, {}) appears in the cage more than once.", x, y);
+            assert!(self.cage_of[x][y] == 0, "cell ({}, {}) is already part of a cage.", x, y);
+        }
+
+        self.cages.push(Cage { cells: List::from_slice(cells), sum: sum as usize });
+        for &(x, y) in cells {
+            self.cage_of[x][y] = self.cages.len() as u16;
+        }
+
+        // Recompute the candidates of the grid upon solving, now that the cells share a cage.
+        self.requeue_board();
+    }
+    /// Add a thermometer, such that the digits of `path` must strictly increase from its first cell, the bulb,
+    /// while solving and verifying.
+    /// 
+    /// Candidates too low to follow the cells before them or too high to precede the cells after them are eliminated
+    /// as the grid is solved. Thermometers may share cells, such as to branch from a common bulb.
+    /// 
+    /// Panics if `path` is empty, holds more than `N` cells, or holds a cell out of bounds or more than once.
+    /// Without the `alloc` feature, this also panics if `N * N` thermometers have already been added.
+    pub fn add_thermo(&mut self, path: &[(usize, usize)]) {
+        assert!(!path.is_empty() && path.len() <= N, "thermometer of {} cells, must hold within 1 to {}.", path.len(), N);
+        #[cfg(not(feature = "alloc"))]
+        assert!(self.thermos.len() < N * N, "too many thermometers, at most {} fit without the `alloc` feature.", N * N);
+        for (i, &(x, y)) in path.iter().enumerate() {
+            assert!(x < N && y < N, "cell index ({}, {}) out of bounds, must be within 0 to {}.", x, y, N - 1);
+            assert!(!path[..i].contains(&(x, y)), "cell ({}, {}) appears on the thermometer more than once.", x, y);
+        }
+
+        self.thermos.push(Thermo { cells: List::from_slice(path) });
+
+        // Recompute the candidates of the grid upon solving, now that the cells are ordered.
+        self.requeue_board();
+    }
+    /// Add an inequality between two cells, such that the digit of `a` must be less than that of `b`, as with
+    /// the signs of a greater-than sudoku, while solving and verifying.
+    /// 
+    /// This is held as a thermometer of the two cells, with candidates pruned and verified in the same way.
+    /// Panics if either cell is out of bounds, or if `a` and `b` are the same cell.
+    pub fn add_inequality(&mut self, a: (usize, usize), b: (usize, usize)) {
+        self.add_thermo(&[a, b]);
+    }
+
+    /// Returns the grid rotated a quarter turn clockwise.
+    /// 
+    /// As with the other transforms, only the digits and givens of the grid are kept, while its constraints are carried over.
+    pub fn rotate90(&self) -> Self {
+        self.transformed(|x, y, digit| ((N - 1 - y, x), digit))
+    }
+    /// Returns the grid mirrored from left to right.
+    pub fn reflect_horizontal(&self) -> Self {
+        self.transformed(|x, y, digit| ((N - 1 - x, y), digit))
+    }
+    /// Returns the grid mirrored about its leading diagonal, such that rows become columns.
+    pub fn transpose(&self) -> Self {
+        self.transformed(|x, y, digit| ((y, x), digit))
+    }
+    /// Returns the grid with each digit `d` replaced by `perm[d - 1]`.
+    /// 
+    /// Killer cages and thermometers are discarded, as their sums and orders do not hold for the relabeled digits.
+    /// Panics if `perm` is not a permutation of the digits from 1 to `N`.
+    pub fn relabel(&self, perm: [u8; N]) -> Self {
+        let digits = perm.iter().fold(0u32, |digits, &digit| digits | 1 << digit);
+        assert!(digits == (Self::MAYBE_ALL as u32) << 1, "relabeling {:?} is not a permutation of the digits.", perm);
+
+        let mut grid = self.transformed(|x, y, digit| ((x, y), if digit == 0 { 0 } else { perm[digit as usize - 1] }));
+        grid.cages.clear();
+        grid.cage_of = [[0; N]; N];
+        grid.thermos.clear();
+        grid
+    }
+    fn transformed(&self, transform: impl Fn(usize, usize, u8) -> ((usize, usize), u8)) -> Self {
+        //! Returns a grid of the digits moved and replaced by `transform`, keeping the grid's constraints.
+
+        let board = self.board();
+        let mut grid = self.clone();
+        grid.reset();
+        for y in 0..N {
+            for x in 0..N {
+                let ((tx, ty), digit) = transform(x, y, board[x][y]);
+                if digit != 0 {
+                    grid.set_cells.push((digit as usize, (tx, ty)));
+                }
+                grid.givens[tx][ty] = self.givens[x][y];
+                grid.blocks[tx][ty] = self.blocks[x][y];
+            }
+        }
+        grid.index_blocks();
+
+        // Move the cells of the cages along with their digits.
+        grid.cage_of = [[0; N]; N];
+        for (c, cage) in grid.cages.iter_mut().enumerate() {
+            for cell in cage.cells.iter_mut() {
+                *cell = transform(cell.0, cell.1, 0).0;
+                grid.cage_of[cell.0][cell.1] = c as u16 + 1;
+            }
+        }
+        for cell in grid.thermos.iter_mut().flat_map(|thermo| thermo.cells.iter_mut()) {
+            *cell = transform(cell.0, cell.1, 0).0;
+        }
+        grid
+    }
+
+    /// Serialize grid into a string of `N * N` characters, a cell each in row-major order.
+    /// Digits are given from `1` to `9` followed by letters from `A`, and blank cells as `0`, such that a standard
+    /// grid produces 81 ASCII digits from 0 to 9.
+    #[cfg(feature = "alloc")]
+    pub fn to_str(&self) -> String {
+        self.to_str_with_blank('0')
+    }
+    /// Serialize the grid as `to_str` does, with blank cells given as `blank`, such as `.`.
+    /// 
+    /// The result can be parsed again with `from_str_with_blank`.
+    /// Panics if `blank` is an ASCII digit other than `0`, or a letter standing for a digit of the grid, which would
+    /// be mistaken for a set cell.
+    #[cfg(feature = "alloc")]
+    pub fn to_str_with_blank(&self, blank: char) -> String {
+        assert!(
+            !(blank.is_ascii_digit() && blank != '0') && blank.to_digit(N as u32 + 1).is_none_or(|digit| digit == 0),
+            "blank character {:?} is a digit.", blank
+        );
+
+        let mut str = String::with_capacity(N * N);
+        let board = self.board();
+
+        for y in 0..N {
+            for x in 0..N {
+                str.push(Self::digit_char(board[x][y], blank));
+            }
+        }
+
+        str
+    }
+    /// Serialize the candidates of each cell as rows of cells separated by spaces, one row per line.
+    /// 
+    /// Each cell lists its candidates in ascending order, such as `139`, or its digit if set, or `.` if no
+    /// candidates remain. Cells are padded to the width of the widest cell, such that columns line up.
+    /// The candidates are those the solver has yet to eliminate, see `candidates`.
+    #[cfg(feature = "alloc")]
+    pub fn candidates_str(&self) -> String {
+        let board = self.board();
+        let maybe = self.settled_maybe();
+        let marks = |x: usize, y: usize| match board[x][y] {
+            0 => maybe[x][y],
+            digit => 1 << (digit - 1),
+        };
+        let width = (0..N * N).map(|i| marks(i % N, i / N).count_ones()).max().unwrap_or(0).max(1) as usize;
+
+        let mut str = String::with_capacity(N * N * (width + 1));
+        for y in 0..N {
+            if y != 0 {
+                str.push('\n');
+            }
+            for x in 0..N {
+                let maybes = marks(x, y);
+                if maybes == 0 {
+                    str.push('.');
+                }
+                for di in 0..N {
+                    if maybes & 1 << di != 0 {
+                        str.push(Self::digit_char(di as u8 + 1, '.'));
+                    }
+                }
+                if x != N - 1 {
+                    str.extend(core::iter::repeat_n(' ', width + 1 - (maybes.count_ones() as usize).max(1)));
+                }
+            }
+        }
+
+        str
+    }
+    /// Encode the puzzle in the DIMACS CNF format, as accepted by most SAT solvers.
+    /// 
+    /// Variable `(y * N + x) * N + digit` is true if the cell at `(x, y)` holds `digit`, giving 729 variables for a
+    /// standard grid. Each cell holds at least one and at most one digit, each unit holds each digit at least once
+    /// and at most once, and each digit of the grid is given as a unit clause. The units include irregular regions
+    /// and those added by constraints, while cells a knight's move apart differ with `Constraint::AntiKnight`.
+    /// Killer cages and thermometers are not encoded.
+    #[cfg(feature = "alloc")]
+    pub fn to_cnf(&self) -> String {
+        use core::fmt::Write;
+
+        let board = self.board();
+        let var = |(x, y): (usize, usize), di: usize| ((y * N + x) * N + di + 1) as isize;
+        let mut clauses: Vec<Vec<isize>> = Vec::new();
+        let mut exactly_one = |vars: &[isize]| {
+            clauses.push(vars.to_vec());
+            for (i, &a) in vars.iter().enumerate() {
+                for &b in &vars[i + 1..] {
+                    clauses.push(alloc::vec![-a, -b]);
+                }
+            }
+        };
+
+        for i in 0..N * N {
+            let cell = (i % N, i / N);
+            exactly_one(&(0..N).map(|di| var(cell, di)).collect::<Vec<_>>());
+        }
+        for unit in 0..self.unit_count() {
+            let cells = self.unit_cells(unit);
+            for di in 0..N {
+                exactly_one(&cells.iter().map(|&cell| var(cell, di)).collect::<Vec<_>>());
+            }
+        }
+        if self.anti_knight {
+            for i in 0..N * N {
+                let cell = (i % N, i / N);
+                // Each pair of cells is encoded once, from the earlier cell in row-major order.
+                for knight in Self::knight_moves(cell).filter(|&(x, y)| y * N + x > i) {
+                    for di in 0..N {
+                        clauses.push(alloc::vec![-var(cell, di), -var(knight, di)]);
+                    }
+                }
+            }
+        }
+        for i in 0..N * N {
+            let (x, y) = (i % N, i / N);
+            if board[x][y] != 0 {
+                clauses.push(alloc::vec![var((x, y), board[x][y] as usize - 1)]);
+            }
+        }
+
+        let mut cnf = String::new();
+        let _ = writeln!(cnf, "p cnf {} {}", N * N * N, clauses.len());
+        for clause in clauses {
+            for literal in clause {
+                let _ = write!(cnf, "{} ", literal);
+            }
+            cnf.push_str("0\n");
+        }
+        cnf
+    }
+    fn digit_char(digit: u8, blank: char) -> char {
+        //! Returns the character of a digit from 1 to `N`, using uppercase letters for digits above 9, or `blank` if
+        //! the cell is blank. Digits out of range cannot occur, but are rendered as blank rather than panicking
+        //! regardless.
+
+        debug_assert!(digit as usize <= N, "grid contains invalid cell {}.", digit);
+        match digit {
+            0 => blank,
+            digit => char::from_digit(digit as u32, N as u32 + 1).map_or(blank, |char| char.to_ascii_uppercase()),
+        }
+    }
+    fn board(&self) -> [[u8; N]; N] {
+        //! Returns the digits of each cell, including those that are pending being set.
+
+        let mut board = self.cells;
+        // Pending cells are set in the order the solver would, any later conflicting sets are discarded.
+        for (digit, (x, y)) in self.set_cells.iter().rev() {
+            if board[x][y] == 0 {
+                board[x][y] = digit as u8;
+            }
+        }
+        board
+    }
+
+    /// Returns the digit of the cell at `(x, y)`, zero if blank.
+    ///
+    /// Panics if `x` or `y` is not within 0 to `N - 1`; see `get_checked` for a non-panicking alternative.
+    pub fn get(&self, x: usize, y: usize) -> u8 {
+        self.get_checked(x, y)
+            .unwrap_or_else(|| panic!("cell index ({}, {}) out of bounds, must be within 0 to {}.", x, y, N - 1))
+    }
+    /// Returns the digit of the cell at `(x, y)`, zero if blank, or `None` if the index is out of bounds.
+    pub fn get_checked(&self, x: usize, y: usize) -> Option<u8> {
+        self.board().get(x)?.get(y).copied()
+    }
+    /// Returns the digits of the cells indexed by `[x][y]`, zero if blank, as `into_cells` does without consuming the grid.
+    pub fn to_cells(&self) -> [[u8; N]; N] {
+        self.board()
+    }
+    /// Returns the digits of the cells indexed by `[x][y]`, zero if blank, as accepted by `from_cells`.
+    pub fn into_cells(self) -> [[u8; N]; N] {
+        self.board()
+    }
+    /// Returns the digits of row `y` from left to right, zero if blank, as with `get`.
+    /// 
+    /// Panics if `y` is not within 0 to `N - 1`.
+    pub fn row(&self, y: usize) -> [u8; N] {
+        assert!(y < N, "row {} out of bounds, must be within 0 to {}.", y, N - 1);
+        let board = self.board();
+        core::array::from_fn(|x| board[x][y])
+    }
+    /// Returns the digits of column `x` from top to bottom, zero if blank, as with `get`.
+    /// 
+    /// Panics if `x` is not within 0 to `N - 1`.
+    pub fn col(&self, x: usize) -> [u8; N] {
+        assert!(x < N, "column {} out of bounds, must be within 0 to {}.", x, N - 1);
+        self.board()[x]
+    }
+    /// Returns the digits of block `blk` in row-major order, zero if blank, as with `get`.
+    /// 
+    /// Blocks are numbered in row-major order, from 0 at the top left to `N - 1` at the bottom right.
+    /// With irregular regions, see `with_regions`, these are the digits of region `blk`.
+    /// Panics if `blk` is not within 0 to `N - 1`.
+    pub fn block(&self, blk: usize) -> [u8; N] {
+        assert!(blk < N, "block {} out of bounds, must be within 0 to {}.", blk, N - 1);
+        let board = self.board();
+        self.block_cells[blk].map(|(x, y)| board[x][y])
+    }
+    /// Returns the cells given by the puzzle as `(x, y, digit)`, in the same order as `to_str`.
+    /// 
+    /// Givens are the digits the grid was parsed or constructed from, as opposed to those placed by the solver.
+    pub fn givens(&self) -> impl Iterator<Item = (usize, usize, u8)> {
+        let (board, givens) = (self.board(), self.givens);
+        (0..N * N)
+            .map(|i| (i % N, i / N))
+            .filter(move |&(x, y)| givens[x][y])
+            .map(move |(x, y)| (x, y, board[x][y]))
+    }
+    /// Returns the number of cells given by the puzzle, which is unaffected by solving.
+    pub fn clue_count(&self) -> usize {
+        self.givens.iter().flatten().filter(|&&given| given).count()
+    }
+    /// Returns the number of cells set so far, out of `N * N`, such as to report the progress of a solve.
+    pub fn filled_count(&self) -> usize {
+        self.board().iter().flatten().filter(|&&digit| digit != 0).count()
+    }
+    /// Check whether every cell holds a digit, without checking that the digits are a valid solution.
+    pub fn is_solved(&self) -> bool {
+        self.board().iter().flatten().all(|&digit| digit != 0)
+    }
+    /// Returns the first blank cell in row-major order, or `None` if every cell holds a digit.
+    pub fn first_empty(&self) -> Option<(usize, usize)> {
+        let board = self.board();
+        (0..N * N).map(|i| (i % N, i / N)).find(|&(x, y)| board[x][y] == 0)
+    }
+    /// Returns the bitmap of digits the solver still considers possible for the cell at `(x, y)`.
+    ///
+    /// Bit `n` is set if digit `n + 1` is a candidate. Cells that have been set have no candidates. Digits not yet
+    /// taken by the solver, such as the givens of a freshly parsed grid, are eliminated from their peers first,
+    /// while grids whose digits contradict each other have no candidates at all.
+    /// Panics if the index is out of bounds.
+    pub fn candidates(&self, x: usize, y: usize) -> u16 {
+        assert!(x < N && y < N, "cell index ({}, {}) out of bounds, must be within 0 to {}.", x, y, N - 1);
+        self.settled_maybe()[x][y]
+    }
+    /// Returns the digits the solver still considers possible for the cell at `(x, y)` in ascending order.
+    pub fn candidate_digits(&self, x: usize, y: usize) -> impl Iterator<Item = u8> {
+        let maybes = self.candidates(x, y);
+        (1..=N as u8).filter(move |digit| maybes & 1 << (digit - 1) != 0)
+    }
+    /// Returns the cells sharing a row, column, or block with the cell at `(x, y)` in row-major order, excluding itself.
+    /// 
+    /// This is 20 cells for a standard grid. The diagonals and windows of grids with `Constraint::Diagonals` and
+    /// `Constraint::Windoku`, the cells a knight's move away with `Constraint::AntiKnight`, and irregular regions
+    /// given by `with_regions` are accounted for.
+    /// Panics if the cell is out of bounds.
+    pub fn peers(&self, x: usize, y: usize) -> impl Iterator<Item = (usize, usize)> + '_ {
+        let block = self.blocks[x][y];
+        (0..N * N).map(|i| (i % N, i / N)).filter(move |&(px, py)| {
+            (px, py) != (x, y) && (px == x || py == y || self.blocks[px][py] == block
+                || self.extra_units_of[x][y] & self.extra_units_of[px][py] != 0
+                || self.anti_knight && Self::knight_apart((x, y), (px, py)))
+        })
+    }
+
+    /// Place `digit` at `(x, y)`, eliminating it from the candidates of the cell's peers without deducing further digits.
+    /// 
+    /// If the digit conflicts with the grid, or leaves a cell without candidates, a contradiction is returned instead.
+    pub fn place(&mut self, x: usize, y: usize, digit: u8) -> Result<(), PlaceError> {
+        if x >= N || y >= N {
+            return Err(PlaceError::OutOfBounds { x, y });
+        }
+        if digit == 0 || digit as usize > N {
+            return Err(PlaceError::InvalidDigit(digit));
+        }
+
+        self.settle()?;
+        if self.cells[x][y] != 0 {
+            return Err(PlaceError::Occupied { x, y });
+        }
+
+        let checkpoint = self.trail.len();
+        self.set_cells.push((digit as usize, (x, y)));
+        let result = self.settle();
+        if result.is_err() {
+            self.undo(checkpoint);
+        }
+        self.trail.clear();
+        result
+    }
+    /// Remove the digit at `(x, y)`, recomputing the candidates of the grid from its remaining digits,
+    /// see `recompute_candidates`.
+    /// 
+    /// Erasing a blank cell has no effect, while the givens of the puzzle cannot be erased.
+    pub fn erase(&mut self, x: usize, y: usize) -> Result<(), PlaceError> {
+        if x >= N || y >= N {
+            return Err(PlaceError::OutOfBounds { x, y });
+        }
+        if self.givens[x][y] {
+            return Err(PlaceError::Given { x, y });
+        }
+
+        let mut board = self.board();
+        if board[x][y] != 0 {
+            board[x][y] = 0;
+            self.cells = board;
+            self.set_cells.clear();
+            self.recompute_candidates();
+        }
+        Ok(())
+    }
+    /// Rebuild the candidates and digit counters of the grid from its digits, such as after its cells were modified.
+    pub fn recompute_candidates(&mut self) {
+        self.requeue_board();
+
+        // Should the digits contradict each other, they are left pending for solving to report the contradiction.
+        let requeued = self.clone();
+        if self.settle().is_err() {
+            *self = requeued;
+        }
+        self.trail.clear();
+    }
+    fn requeue_board(&mut self) {
+        //! Reset the grid, keeping its constraints, and queue its digits and givens to be placed again,
+        //! such that its candidates are recomputed upon solving.
+
+        let (board, givens) = (self.board(), self.givens);
+        self.reset();
+        self.givens = givens;
+        for y in 0..N {
+            for x in 0..N {
+                if board[x][y] != 0 {
+                    self.set_cells.push((board[x][y] as usize, (x, y)));
+                }
+            }
+        }
+    }
+    fn settled_maybe(&self) -> [[u16; N]; N] {
+        //! Returns the maybes of the grid once its pending operations are taken as `settle` does, settling a copy
+        //! of the grid if any are pending. Contradictory grids have no maybes.
+
+        if self.del_maybes.is_empty() && self.set_cells.is_empty() {
+            return self.maybe;
+        }
+        let mut grid = self.clone();
+        match grid.settle() {
+            Ok(()) => grid.maybe,
+            Err(_) => [[0; N]; N],
+        }
+    }
+    fn settle(&mut self) -> Result<(), PlaceError> {
+        //! Take the pending operations without deducing any further digits, such that the candidates of
+        //! the grid reflect the digits placed. Cells left without candidates are a contradiction.
+
+        loop {
+            if let Some((digit, (x, y))) = self.del_maybes.pop() {
+                if self.maybe[x][y] & 1 << (digit - 1) != 0 {
+                    if self.update_counters(digit, (x, y)).is_err() {
+                        return Err(PlaceError::Contradiction { x, y, digit: digit as u8 });
+                    }
+                    self.maybe[x][y] &= !(1 << (digit - 1));
+                    if self.maybe[x][y] == 0 {
+                        return Err(PlaceError::Contradiction { x, y, digit: digit as u8 });
+                    }
+                }
+            } else if let Some((digit, index)) = self.set_cells.pop() {
+                if let Err(SolveError::Contradiction { x, y, digit }) = self.set_cell(digit, index) {
+                    return Err(PlaceError::Contradiction { x, y, digit });
+                }
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Check whether the grid is in a valid solved state or not.
+    pub fn verify_solution(&self) -> bool {
+
+        // Check for exhaustion of maybes/available blocks
+        if self.maybe              != [[0u16; N]; N]
+        || self.row_digit_counters != [[0u8; N]; N]
+        || self.col_digit_counters != [[0u8; N]; N] 
+        || self.blk_digit_counters != [[0u8; N]; N]
+        || self.extra_digit_counters.iter().any(|counters| *counters != [0u8; N]) {
+            return false;
+        }
+
+        self.is_valid_solution()
+    }
+    /// Check whether every cell holds a digit, with each row, column, and block containing every digit, as well as
+    /// the diagonals and windows of grids with `Constraint::Diagonals` and `Constraint::Windoku`, and no cells a
+    /// knight's move apart sharing a digit with `Constraint::AntiKnight`.
+    /// 
+    /// Unlike `verify_solution`, this only considers the digits of the cells, so it may be used on any grid.
+    pub fn is_valid_solution(&self) -> bool {
+        let board = self.board();
+
+        // Ensure there is one of each digit in every row, column, and block
+        let mut row_digit_counters = [[0u8; N]; N];
+        let mut col_digit_counters = [[0u8; N]; N];
+        let mut blk_digit_counters = [[0u8; N]; N];
+        
+        for y in 0..N {
+            for x in 0..N {
+                if board[x][y] == 0 { return false; }
+
+                row_digit_counters[y                      ][board[x][y] as usize - 1] += 1;
+                col_digit_counters[x                      ][board[x][y] as usize - 1] += 1;
+                blk_digit_counters[self.blocks[x][y] as usize][board[x][y] as usize - 1] += 1;
+            }
+        }
+
+        if row_digit_counters != [[1u8; N]; N]
+        || col_digit_counters != [[1u8; N]; N]
+        || blk_digit_counters != [[1u8; N]; N] {
+            return false;
+        }
+
+        let extra_units = self.extra_units.iter().all(|cells| {
+            cells.iter().fold(0u16, |seen, &(x, y)| seen | 1 << (board[x][y] - 1)) == Self::MAYBE_ALL
+        });
+        extra_units && self.cages.iter().all(|cage| cage.is_complete(&board))
+            && self.thermos.iter().all(|thermo| thermo.fits(&board)) && self.knights_differ(&board)
+    }
+
+    /// Check whether no row, column, or block contains the same digit more than once, ignoring blank cells.
+    /// 
+    /// Unlike `is_valid_solution`, the grid need not be complete.
+    /// Killer cages must neither repeat a digit nor exceed their sums, counting the least digits their blank cells could
+    /// still hold, and must add up to them once complete.
+    /// The digits along thermometers must increase, leaving enough digits for the blank cells around them.
+    pub fn is_consistent(&self) -> bool {
+        let board = self.board();
+
+        for unit in 0..self.unit_count() {
+            let mut seen = 0u16;
+            for (x, y) in self.unit_cells(unit) {
+                if board[x][y] != 0 {
+                    if seen & 1 << (board[x][y] - 1) != 0 {
+                        return false;
+                    }
+                    seen |= 1 << (board[x][y] - 1);
+                }
+            }
+        }
+
+        self.cages.iter().all(|cage| {
+            let mut seen = 0u16;
+            let mut sum = 0;
+            for &(x, y) in &cage.cells {
+                if board[x][y] != 0 {
+                    if seen & 1 << (board[x][y] - 1) != 0 {
+                        return false;
+                    }
+                    seen |= 1 << (board[x][y] - 1);
+                    sum += board[x][y] as usize;
+                }
+            }
+
+            // Blank cells hold distinct digits not yet in the cage, adding at least the smallest of them.
+            let blanks = cage.cells.len() - seen.count_ones() as usize;
+            let least = (1..=N).filter(|&digit| seen & 1 << (digit - 1) == 0).take(blanks).sum::<usize>();
+            sum + least == cage.sum || blanks > 0 && sum + least < cage.sum
+        }) && self.thermos.iter().all(|thermo| thermo.fits(&board)) && self.knights_differ(&board)
+    }
+    fn knights_differ(&self, board: &[[u8; N]; N]) -> bool {
+        //! Check whether no two cells a knight's move apart hold the same digit, ignoring blank cells,
+        //! or trivially so unless `anti_knight` is set.
+
+        !self.anti_knight || (0..N * N).map(|i| (i % N, i / N)).all(|(x, y)| {
+            board[x][y] == 0 || Self::knight_moves((x, y)).all(|(kx, ky)| board[kx][ky] != board[x][y])
+        })
+    }
+
+    /// Attempt to solve the grid, returning `Ok(())` on success and `Err(SolveError::Contradiction)` on failure.
+    /// 
+    /// The error holds the cell and digit at which the contradiction ending the search was reached.
+    /// Every possibility is searched before failing, such that an error guarantees the grid has no solution.
+    /// A grid given complete is checked as is, with any duplicate digit reported as a contradiction.
+    /// An empty grid is solved to an arbitrary completion, which takes no backtracking as every guess succeeds.
+    /// 
+    /// Grids with several solutions are solved to the first one found. Guesses are made at the cell with the fewest
+    /// candidates, the first such cell in row-major order, trying its candidates from the lowest digit up.
+    /// This order is stable, such that a grid is always solved to the same solution, the first yielded by `solutions`.
+    /// It leaves the grid in an undefined state, however `verify_solution` will still give an accurate result.
+    /// 
+    /// It may be desired to call `verify_solution` on the grid hereafter, however this shouldn't be necessary.
+    pub fn solve(&mut self) -> Result<(), SolveError> {
+        self.solve_with_stats().map(|_| ())
+    }
+    /// Attempt to solve a copy of the grid as `solve` does, returning the solution and leaving the grid untouched.
+    pub fn solved(&self) -> Result<Self, SolveError> {
+        let mut grid = self.clone();
+        grid.solve()?;
+        Ok(grid)
+    }
+    /// Attempt to solve the grid as `solve` does, additionally counting the work done by the solver.
+    pub fn solve_with_stats(&mut self) -> Result<SolveStats, SolveError> {
+        let mut stats = SolveStats::default();
+        self.search(&SolverConfig::default(), &mut stats, &mut |_| {})?;
+        Ok(stats)
+    }
+    /// Attempt to solve the grid as `solve` does, exploring the possibilities of the first few guesses in parallel.
+    /// 
+    /// Each possibility of a guessed cell is searched concurrently, taking the first solution found, such that grids
+    /// with several solutions may be solved differently between runs. If the grid has no solution, the contradiction
+    /// returned is one reached by any of the possibilities searched.
+    #[cfg(feature = "rayon")]
+    pub fn solve_parallel(&mut self) -> Result<(), SolveError> {
+        *self = *Box::new(self.clone()).search_parallel(Self::PARALLEL_DEPTH)?;
+        Ok(())
+    }
+    /// Depth of guesses beyond which branches are searched sequentially, as spawning more is not worthwhile.
+    #[cfg(feature = "rayon")]
+    const PARALLEL_DEPTH: usize = 3;
+    #[cfg(feature = "rayon")]
+    fn search_parallel(mut self: Box<Self>, depth: usize) -> Result<Box<Self>, SolveError> {
+        //! Solve the grid, searching each possibility of the next guess in parallel until `depth` guesses deep.
+        //! 
+        //! Grids are boxed, as the worker threads would otherwise hold a copy of each branch's grid on their stacks.
+
+        use rayon::prelude::*;
+
+        self.propagate()?;
+        if self.maybe == [[0; N]; N] {
+            return Ok(self);
+        }
+        if depth == 0 {
+            self.solve()?;
+            return Ok(self);
+        }
+
+        // Solutions are passed as the error of each branch, such that the first one found ends the search,
+        // while the contradiction of a failed branch is reported should every branch fail.
+        let index = self.choose_guess_cell().unwrap();
+        let maybes = self.maybe[index.0][index.1];
+        let result = (0..N)
+            .into_par_iter()
+            .filter(|di| maybes & 1 << di != 0)
+            .map(|di| {
+                let mut guess = self.clone();
+                guess.trail.clear();
+                guess.set_cells.push((di + 1, index));
+                match guess.search_parallel(depth - 1) {
+                    Ok(solution) => Err(solution),
+                    Err(err) => Ok(err),
+                }
+            })
+            .try_reduce_with(|_, err| Ok(err));
+
+        match result.expect("the guessed cell has candidates") {
+            Ok(err) => Err(err),
+            Err(solution) => Ok(solution),
+        }
+    }
+    /// Attempt to solve the grid as `solve` does, trying the possibilities of each guess in an order chosen by `rng`.
+    /// 
+    /// The search remains exhaustive, although grids with several solutions may be solved to any of them.
+    pub fn solve_seeded(&mut self, rng: &mut impl RngCore) -> Result<(), SolveError> {
+        self.fill_random(rng).map(|_| ())
+    }
+    /// Attempt to solve the grid as `solve` does, using only the techniques enabled by `config`.
+    /// 
+    /// If `config` disallows guessing and the techniques cannot complete the grid, `SolveError::Stuck` is returned,
+    /// with the grid left in the state that was reached.
+    pub fn solve_with(&mut self, config: &SolverConfig) -> Result<(), SolveError> {
+        self.search(config, &mut SolveStats::default(), &mut |_| {})
+    }
+    /// Attempt to solve the grid as `solve` does, giving up with `Err(SolveBudgetError::Exhausted)` once `max_steps`
+    /// steps have been taken, such as to bound the time spent on adversarial puzzles.
+    /// 
+    /// Each round of logical deductions between guesses is a step, as is each guess, including those made after
+    /// backtracking. A puzzle solved by logic alone thus takes a single step, while the work done per step is bounded
+    /// by the size of the grid.
+    pub fn solve_bounded(&mut self, max_steps: usize) -> Result<(), SolveBudgetError> {
+        let config = SolverConfig { max_steps, ..SolverConfig::default() };
+        match self.search(&config, &mut SolveStats::default(), &mut |_| {}) {
+            Ok(()) => Ok(()),
+            Err(SolveError::Contradiction { x, y, digit }) => Err(SolveBudgetError::Contradiction { x, y, digit }),
+            // Guessing is allowed, so the search only gets stuck once it runs out of steps.
+            Err(SolveError::Stuck) => Err(SolveBudgetError::Exhausted),
+            Err(SolveError::NotUnique) => unreachable!("the search does not check for uniqueness"),
+        }
+    }
+    /// Attempt to solve the grid as `solve` does, calling `observer` on each event as the solve progresses.
+    /// 
+    /// Cells set and candidates eliminated are observed in the order the solver made the changes.
+    pub fn solve_with_observer(&mut self, observer: &mut impl FnMut(&StepEvent)) -> Result<(), SolveError> {
+        self.search(&SolverConfig::default(), &mut SolveStats::default(), observer)
+    }
+    fn find_conflict(&self, board: &[[u8; N]; N]) -> (usize, usize) {
+        //! Returns the first cell of a complete but invalid board in row-major order that shares its digit
+        //! with a peer, or failing that, the first cell of a cage that does not add up to its sum or of a thermometer
+        //! that does not increase.
+
+        (0..N * N).map(|i| (i % N, i / N))
+            .find(|&(x, y)| self.peers(x, y).any(|(px, py)| board[px][py] == board[x][y]))
+            .or_else(|| self.cages.iter().find(|cage| !cage.is_complete(board)).map(|cage| cage.cells[0]))
+            .or_else(|| self.thermos.iter().find(|thermo| !thermo.fits(board)).map(|thermo| thermo.cells[0]))
+            .unwrap_or((0, 0))
+    }
+    fn search(
+        &mut self,
+        config: &SolverConfig,
+        stats: &mut SolveStats,
+        observer: &mut impl FnMut(&StepEvent),
+    ) -> Result<(), SolveError> {
+        //! Solve the grid as configured, guessing if allowed, counting the work done into `stats`
+        //! and passing each change to `observer`.
+
+        // A complete board leaves nothing to search, so it need only be checked against the rules.
+        let board = self.board();
+        if board.iter().flatten().all(|&digit| digit != 0) && !self.is_valid_solution() {
+            let (x, y) = self.find_conflict(&board);
+            return Err(SolveError::Contradiction { x, y, digit: board[x][y] });
+        }
+
+        // Grids solved without guessing never push a guess, so need not allocate for them.
+        let mut guesses = Guesses::unreserved();
+        // Rounds of propagation and guesses made, stopping the search once the configured budget runs out.
+        let mut steps = 0;
+
+        loop {
+            if steps >= config.max_steps {
+                return Err(SolveError::Stuck);
+            }
+            steps += 1;
+
+            let checkpoint = self.trail.len();
+            let result = self.propagate_with(config, stats);
+            self.observe_trail(checkpoint, observer);
+
+            if let Err(error) = result {
+                // Hypothetical guess failed, try the next possibility instead.
+                if !self.next_guess(&mut guesses) {
+                    return Err(error);
+                }
+                if let SolveError::Contradiction { x, y, digit } = error {
+                    observer(&StepEvent::Backtrack { digit, x, y });
+                }
+                self.observe_guess(observer);
+                stats.backtracks += 1;
+                stats.guesses += 1;
+                steps += 1;
+                continue;
+            }
+
+            if self.maybe == [[0; N]; N] {
+                // Grid has been solved, return
+                return Ok(());
+            } else if !config.guessing {
+                return Err(SolveError::Stuck);
+            } else {
+                // Guess each possibility of the cell in turn, using process of elimination to find the correct one.
+                let index = self.choose_guess_cell().unwrap();
+                guesses.push((self.trail.len(), index, self.maybe[index.0][index.1]));
+                self.next_guess(&mut guesses);
+                self.observe_guess(observer);
+                stats.guesses += 1;
+                steps += 1;
+            }
+        }
+    }
+    fn observe_trail(&self, checkpoint: usize, observer: &mut impl FnMut(&StepEvent)) {
+        //! Pass the changes recorded on the trail since it was `checkpoint` long to `observer`.
+
+        // Setting a cell clears its remaining candidates, which are not eliminations in their own right.
+        let mut set = None;
+        for change in self.trail.iter().skip(checkpoint) {
+            match change {
+                Change::SetCell((x, y)) => {
+                    set = Some((x, y));
+                    observer(&StepEvent::Set { digit: self.cells[x][y], x, y });
+                },
+                Change::DelMaybe(_, index) if set == Some(index) => {},
+                Change::DelMaybe(digit, (x, y)) => {
+                    set = None;
+                    observer(&StepEvent::Eliminate { digit: digit as u8, x, y });
+                },
+            }
+        }
+    }
+    fn observe_guess(&self, observer: &mut impl FnMut(&StepEvent)) {
+        //! Pass the guess just made by `next_guess` to `observer`.
+
+        let (digit, (x, y)) = self.set_cells.last().unwrap();
+        observer(&StepEvent::Guess { digit: digit as u8, x, y });
+    }
+
+    /// Solve the grid as far as possible using logical techniques alone, never guessing.
+    /// 
+    /// The grid is left in the state that was reached, such that the remaining candidates of a
+    /// `Stuck` grid can be inspected.
+    pub fn solve_logical(&mut self) -> SolveOutcome {
+        match self.propagate() {
+            Ok(_) if self.maybe == [[0; N]; N] => SolveOutcome::Solved,
+            Ok(_) => SolveOutcome::Stuck,
+            Err(SolveError::Contradiction { x, y, digit }) => SolveOutcome::Contradiction { x, y, digit },
+            Err(SolveError::NotUnique | SolveError::Stuck) => unreachable!("propagation only reports contradictions"),
+        }
+    }
+
+    /// Find the next deduction the solver would make, without modifying the grid.
+    /// 
+    /// Only pending eliminations and placements, then hidden singles, are looked for. Returns `None` if there are
+    /// none, which happens once the grid is solved, but also when techniques such as naked pairs, or killer cages
+    /// and thermometers, could still make progress (see `explain`, which applies those too).
+    /// The step can then be taken using `apply_step`.
+    pub fn next_step(&self) -> Option<Step> {
+        // Pending operations are taken in the same order as the solver, skipping any that have no effect.
+        let elimination = self.del_maybes.iter().rev()
+            .find(|&(digit, (x, y))| self.maybe[x][y] & 1 << (digit - 1) != 0);
+        if let Some((digit, (x, y))) = elimination {
+            return Some(Step::Elimination { digit: digit as u8, x, y });
+        }
+
+        let placement = self.set_cells.iter().rev()
+            .find(|&(digit, (x, y))| self.cells[x][y] != digit as u8);
+        if let Some((digit, (x, y))) = placement {
+            return Some(match self.naked_single((x, y)) {
+                Some(single) if single == digit && !self.givens[x][y] => Step::NakedSingle { digit: digit as u8, x, y },
+                _ => Step::Placement { digit: digit as u8, x, y },
+            });
+        }
+
+        let mut hypothetical = self.clone();
+        hypothetical.set_cells.clear();
+        hypothetical.find_hidden_singles();
+        let (digit, (x, y)) = hypothetical.set_cells.pop()?;
+
+        // Report the first of the cell's units in which no other cell may hold the digit.
+        let unit = (0..self.unit_count()).find(|&unit| {
+            let cells = self.unit_cells(unit);
+            cells.contains(&(x, y))
+                && cells.iter().filter(|&&(cx, cy)| self.maybe[cx][cy] & 1 << (digit - 1) != 0).count() == 1
+        })?;
+        Some(Step::HiddenSingle { digit: digit as u8, x, y, unit: self.unit_kind(unit) })
+    }
+    /// Take a step as returned by `next_step`, returning `Err(SolveError::Contradiction)` if this results in a contradiction.
+    pub fn apply_step(&mut self, step: Step) -> Result<(), SolveError> {
+        match step {
+            Step::Placement { digit, x, y }
+            | Step::NakedSingle { digit, x, y }
+            | Step::HiddenSingle { digit, x, y, .. } => self.set_cell(digit as usize, (x, y)),
+            Step::Elimination { digit, x, y }
+            | Step::TechniqueElimination { digit, x, y, .. }
+            | Step::ConstraintElimination { digit, x, y } => self.del_maybe(digit as usize, (x, y)),
+        }
+    }
+    /// Solve a copy of the grid by logic alone, returning every step taken in order.
+    /// 
+    /// Eliminations made by techniques, and by killer cages and thermometers, are reported as such rather than
+    /// as plain eliminations. Returns `Err(SolveError::Stuck)` if the puzzle cannot be solved without guessing,
+    /// or `Err(SolveError::Contradiction)` if it has no solution.
+    #[cfg(feature = "alloc")]
+    pub fn explain(&self) -> Result<Vec<Step>, SolveError> {
+        let mut grid = self.clone();
+        let mut steps = Vec::new();
+        loop {
+            while let Some(step) = grid.next_step() {
+                grid.apply_step(step)?;
+                steps.push(step);
+            }
+            if grid.maybe == [[0; N]; N] {
+                return Ok(steps);
+            }
+
+            // Anything left pending has no effect, so whatever is pending next was found by a single source.
+            let effective = |grid: &Self| grid.del_maybes.iter().any(|(digit, (x, y))| grid.maybe[x][y] & 1 << (digit - 1) != 0);
+            grid.del_maybes.clear();
+            grid.set_cells.clear();
+            grid.find_cage_eliminations()?;
+            grid.find_thermo_eliminations()?;
+            let mut technique = None;
+            if !effective(&grid) {
+                // Hidden singles are already reported by `next_step`.
+                technique = Technique::ALL[1..].iter().copied().find(|&technique| {
+                    grid.del_maybes.clear();
+                    grid.apply_technique(technique);
+                    effective(&grid)
+                });
+                if technique.is_none() {
+                    return Err(SolveError::Stuck);
+                }
+            }
+
+            // Report the deductions all at once, as placements they lead to may leave others without effect.
+            while let Some((digit, (x, y))) = grid.del_maybes.pop() {
+                if grid.maybe[x][y] & 1 << (digit - 1) != 0 {
+                    let digit = digit as u8;
+                    let step = match technique {
+                        Some(technique) => Step::TechniqueElimination { digit, x, y, technique },
+                        None => Step::ConstraintElimination { digit, x, y },
+                    };
+                    grid.apply_step(step)?;
+                    steps.push(step);
+                }
+            }
+        }
+    }
+    /// Make a single step of solving the grid by logic, as `solve_logical` would, reporting what was done.
+    /// 
+    /// Pending eliminations and placements are made one at a time, skipping any that have no effect. Once none are
+    /// left, the techniques are tried in turn until one finds deductions, which are then left pending. Repeated calls
+    /// thus solve the grid as far as logic allows, until `Solved`, `NoProgress`, or `Contradiction` is returned.
+    pub fn step(&mut self) -> StepResult {
+        let contradiction = |err| match err {
+            SolveError::Contradiction { x, y, digit } => StepResult::Contradiction { x, y, digit },
+            SolveError::NotUnique | SolveError::Stuck => unreachable!("propagation only reports contradictions"),
+        };
+
+        while let Some((digit, (x, y))) = self.del_maybes.pop() {
+            if self.maybe[x][y] & 1 << (digit - 1) != 0 {
+                return match self.del_maybe(digit, (x, y)) {
+                    Ok(()) => StepResult::Eliminated { digit: digit as u8, x, y },
+                    Err(err) => contradiction(err),
+                };
+            }
+        }
+        while let Some((digit, (x, y))) = self.set_cells.pop() {
+            if self.cells[x][y] != digit as u8 {
+                return match self.set_cell(digit, (x, y)) {
+                    Ok(()) => StepResult::Placed { digit: digit as u8, x, y },
+                    Err(err) => contradiction(err),
+                };
+            }
+        }
+
+        if self.maybe == [[0; N]; N] {
+            return StepResult::Solved;
+        }
+        if let Err(err) = self.find_cage_eliminations() {
+            return contradiction(err);
+        }
+        if !self.del_maybes.is_empty() {
+            return StepResult::CageSums;
+        }
+        if let Err(err) = self.find_thermo_eliminations() {
+            return contradiction(err);
+        }
+        if !self.del_maybes.is_empty() {
+            return StepResult::Thermometers;
+        }
+        for technique in Technique::ALL {
+            self.apply_technique(technique);
+            if !self.del_maybes.is_empty() || !self.set_cells.is_empty() {
+                return StepResult::Applied(technique);
+            }
+        }
+        StepResult::NoProgress
+    }
+    /// Returns every hidden single currently available as `(digit, x, y)` in row-major order, without modifying the grid.
+    /// 
+    /// A cell that is a hidden single in several of its units is listed once. Contradictory grids have none.
+    #[cfg(feature = "alloc")]
+    pub fn list_hidden_singles(&self) -> Vec<(u8, usize, usize)> {
+        let mut hypothetical = self.clone();
+        if hypothetical.settle().is_err() {
+            return Vec::new();
+        }
+        hypothetical.find_hidden_singles();
+
+        let mut singles: Vec<_> = hypothetical.set_cells.iter().map(|(digit, (x, y))| (digit as u8, x, y)).collect();
+        singles.sort_by_key(|&(digit, x, y)| (y, x, digit));
+        singles.dedup();
+        singles
+    }
+
+    /// Returns every candidate ruled out by a digit of its unit but not yet removed, as `(digit, x, y)` in row-major order.
+    /// 
+    /// Grids that are not `is_consistent` have none.
+    #[cfg(feature = "alloc")]
+    pub fn pending_eliminations(&self) -> Vec<(u8, usize, usize)> {
+        if !self.is_consistent() {
+            return Vec::new();
+        }
+        let mut hypothetical = self.clone();
+        while let Some((digit, index)) = hypothetical.set_cells.pop() {
+            if hypothetical.set_cell(digit, index).is_err() {
+                return Vec::new();
+            }
+        }
+
+        let mut eliminations: Vec<_> = hypothetical.del_maybes.iter()
+            .filter(|&(digit, (x, y))| hypothetical.maybe[x][y] & 1 << (digit - 1) != 0)
+            .map(|(digit, (x, y))| (digit as u8, x, y))
+            .collect();
+        eliminations.sort_by_key(|&(digit, x, y)| (y, x, digit));
+        eliminations.dedup();
+        eliminations
+    }
+
+    /// Attempt to solve the grid, distinguishing grids without a solution from those with several.
+    /// 
+    /// On `Err(SolveError::NotUnique)` the grid is left solved, holding the solution `solve` would find.
+    /// On `Err(SolveError::Contradiction)` the grid is left untouched.
+    pub fn solve_checked(&mut self) -> Result<(), SolveError> {
+        let mut solutions = self.solutions();
+        let Some(solution) = solutions.next() else {
+            return Err(solutions.contradiction.expect("a grid without solutions reaches a contradiction"));
+        };
+        let unique = solutions.next().is_none();
+
+        *self = solution;
+        if unique { Ok(()) } else { Err(SolveError::NotUnique) }
+    }
+
+    /// Count the distinct solutions of the grid, stopping once `limit` have been found.
+    /// 
+    /// The grid itself is left untouched. `count_solutions(2)` suffices to tell a unique solution from several.
+    /// Solutions are found one at a time, such that grids with vast numbers of solutions, such as an empty grid,
+    /// are counted in time proportional to `limit` rather than to the number of solutions.
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        self.solutions().take(limit).count()
+    }
+    /// Returns an iterator over every distinct solution of the grid, leaving the grid itself untouched.
+    /// 
+    /// Solutions are searched for lazily, such that dropping the iterator stops the search.
+    /// They are yielded in the order the guesses of `solve` reach them.
+    pub fn solutions(&self) -> Solutions<BOX, N, BOX_HEIGHT> {
+        Solutions { grid: self.clone(), guesses: Guesses::new(), exhausted: false, contradiction: None }
+    }
+    /// Collect up to `max` distinct solutions of the grid, leaving the grid itself untouched.
+    /// 
+    /// Fewer are returned if the grid has fewer solutions, none if it has no solution.
+    #[cfg(feature = "alloc")]
+    pub fn solve_all(&self, max: usize) -> Vec<Self> {
+        self.solutions().take(max).collect()
+    }
+
+    /// Rate the difficulty of solving the grid, leaving the grid itself untouched.
+    /// 
+    /// Grids that have no solution are rated `Guessing`, as logic alone cannot complete them.
+    pub fn difficulty(&self) -> Difficulty {
+        let mut grid = self.clone();
+        match grid.propagate() {
+            Ok(difficulty) if grid.maybe == [[0; N]; N] => difficulty,
+            _ => Difficulty::Guessing,
+        }
+    }
+    /// Check whether logic alone gets stuck before completing the grid, such that solving it requires guessing,
+    /// leaving the grid itself untouched.
+    /// 
+    /// Unlike `difficulty`, grids whose digits are shown to contradict each other by logic alone do not require guessing.
+    pub fn requires_guessing(&self) -> bool {
+        self.clone().solve_logical() == SolveOutcome::Stuck
+    }
+
+    /// Check whether the grid has exactly one solution.
+    /// 
+    /// An already solved grid is unique, while a grid with contradicting digits has no solution at all.
+    pub fn has_unique_solution(&self) -> bool {
+        self.count_solutions(2) == 1
+    }
+    /// Guess whether the grid has exactly one solution, by solving it `trials` times trying possibilities in an
+    /// order chosen by `rng`, see `solve_seeded`.
+    /// 
+    /// This is probabilistic: `false` is only returned once two different solutions are found or if the grid has
+    /// no solution, which is certain, but `true` only means that every trial found the same solution. A grid with
+    /// several solutions may well be reported as likely unique, especially with few trials. This is intended as a
+    /// quick filter ahead of `has_unique_solution`, which is exact but can be much slower on sparse grids.
+    pub fn likely_unique(&self, trials: usize, rng: &mut impl RngCore) -> bool {
+        let mut first = None;
+        for _ in 0..trials {
+            let mut grid = self.clone();
+            if grid.fill_random(rng).is_err() {
+                return false;
+            }
+            match first {
+                None => first = Some(grid.cells),
+                Some(cells) if cells != grid.cells => return false,
+                Some(_) => (),
+            }
+        }
+        true
+    }
+
+    /// Estimate how hard the grid is to solve by guessing, as the average number of guesses needed to solve it
+    /// over a number of searches that guess digits in a random order.
+    /// 
+    /// Grids that logic alone solves need no guesses, while grids without a solution are rated infinitely hard.
+    /// The random order is seeded consistently, such that the estimate for a grid is always the same.
+    pub fn estimate_branch_factor(&self) -> f64 {
+        const SEARCHES: usize = 16;
+
+        let mut rng = SplitMix64(0);
+        let mut total = 0;
+        for _ in 0..SEARCHES {
+            match self.clone().fill_random(&mut rng) {
+                Ok(guesses) => total += guesses,
+                Err(_) => return f64::INFINITY,
+            }
+        }
+        total as f64 / SEARCHES as f64
+    }
+
+    /// Generate a random puzzle with a unique solution and `clues` given digits.
+    /// 
+    /// A random complete grid is filled in, after which cells are cleared in a random order
+    /// for as long as the solution stays unique. Very low clue counts may be unachievable, in which
+    /// case the puzzle with the fewest clues that could be reached is returned instead.
+    pub fn generate(clues: usize, rng: &mut impl RngCore) -> Self {
+        Self::generate_with(clues, Symmetry::None, rng)
+    }
+    /// Generate a random puzzle with a unique solution, with its clues laid out according to `symmetry`.
+    /// 
+    /// Clues are cleared in symmetric pairs for as long as the solution stays unique, such that no pair
+    /// of clues could be cleared from the resulting puzzle.
+    pub fn generate_symmetric(symmetry: Symmetry, rng: &mut impl RngCore) -> Self {
+        Self::generate_with(0, symmetry, rng)
+    }
+    /// Reduce the puzzle to a minimal one with the same unique solution, such that clearing any of its
+    /// remaining givens would allow several solutions.
+    /// 
+    /// The givens are tried in the same order as `to_str`, while digits placed by the solver are discarded.
+    /// Grids without a unique solution are left unchanged.
+    pub fn minimize(&mut self) {
+        let puzzle = |cells: &[[u8; N]; N]| {
+            let mut grid = self.clone();
+            grid.reset();
+            for y in 0..N {
+                for x in 0..N {
+                    if cells[x][y] != 0 {
+                        grid.set_cells.push((cells[x][y] as usize, (x, y)));
+                        grid.givens[x][y] = true;
+                    }
+                }
+            }
+            grid
+        };
+
+        let mut cells = [[0; N]; N];
+        for (x, y, digit) in self.givens() {
+            cells[x][y] = digit;
+        }
+        if !puzzle(&cells).has_unique_solution() {
+            return;
+        }
+
+        for (x, y, digit) in self.givens() {
+            cells[x][y] = 0;
+            if !puzzle(&cells).has_unique_solution() {
+                cells[x][y] = digit;
+            }
+        }
+
+        *self = puzzle(&cells);
+    }
+    fn generate_with(clues: usize, symmetry: Symmetry, rng: &mut impl RngCore) -> Self {
+        //! Generate a puzzle as `generate` does, clearing the cells mapped onto each other by `symmetry` together.
+
+        let mut solution = Self::default();
+        solution.fill_random(rng).expect("an empty grid always has a solution");
+
+        let mut order: [[(usize, usize); N]; N] = core::array::from_fn(|y| core::array::from_fn(|x| (x, y)));
+        shuffle(order.as_flattened_mut(), rng);
+
+        let mut cells = solution.cells;
+        let mut count = N * N;
+        for (x, y) in order.into_iter().flatten() {
+            if count <= clues {
+                break;
+            }
+            if cells[x][y] == 0 {
+                continue; // Already cleared as the image of another cell.
+            }
+
+            let (ix, iy) = match symmetry {
+                Symmetry::None => (x, y),
+                Symmetry::Rotational180 => (N - 1 - x, N - 1 - y),
+                Symmetry::Diagonal => (y, x),
+            };
+
+            let (digit, image) = (cells[x][y], cells[ix][iy]);
+            cells[x][y] = 0;
+            cells[ix][iy] = 0;
+            if Self::from_cells(cells).unwrap().has_unique_solution() {
+                count -= if (ix, iy) == (x, y) { 1 } else { 2 };
+            } else {
+                cells[x][y] = digit;
+                cells[ix][iy] = image;
+            }
+        }
+
+        Self::from_cells(cells).unwrap()
+    }
+
+    fn fill_random(&mut self, rng: &mut impl RngCore) -> Result<usize, SolveError> {
+        //! Solve the grid by guessing random digits, such that any of its solutions may be produced.
+        //! Returns the number of guesses made.
+
+        // Guesses hold the bit of the digit guessed, rather than the possibilities yet to be tried.
+        let mut guesses = Guesses::<N>::new();
+        let mut count = 0;
+
+        loop {
+            if let Err(error) = self.propagate() {
+                // The guessed digit cannot go there, eliminate it and guess again.
+                let (checkpoint, index, bit) = guesses.pop().ok_or(error)?;
+                self.undo(checkpoint);
+                self.del_maybes.push((bit.trailing_zeros() as usize + 1, index));
+                continue;
+            }
+
+            // Guess on the cell with the fewest possibilities, to quickly reach contradictions.
+            let Some(index) = self.choose_guess_cell() else {
+                // No possibilities remain, grid has been solved.
+                return Ok(count);
+            };
+
+            let mut maybes = self.maybe[index.0][index.1];
+            for _ in 0..rng.next_u32() % maybes.count_ones() {
+                maybes &= maybes - 1;
+            }
+            let digit = maybes.trailing_zeros() as usize + 1;
+
+            guesses.push((self.trail.len(), index, 1 << (digit - 1)));
+            self.set_cells.push((digit, index));
+            count += 1;
+        }
+    }
+
+
+    fn propagate(&mut self) -> Result<Difficulty, SolveError> {
+        //! Solve through elimination, hidden singles, naked pairs, pointing pairs, naked triples, hidden pairs and triples,
+        //! X-Wings, and naked quads until no further progress can be made.
+        //! Returns the difficulty of the hardest technique that was used.
+
+        self.propagate_with(&SolverConfig::default(), &mut SolveStats::default())
+    }
+    fn propagate_with(&mut self, config: &SolverConfig, stats: &mut SolveStats) -> Result<Difficulty, SolveError> {
+        //! As `propagate`, using only the techniques enabled by `config`, counting the hidden singles
+        //! and eliminations into `stats`.
+
+        let mut difficulty = Difficulty::Trivial;
+        loop {
+            if let Some((digit, index)) = self.del_maybes.pop() {
+                if self.maybe[index.0][index.1] & 1 << (digit - 1) != 0 {
+                    stats.eliminations += 1;
+                }
+                self.del_maybe(digit, index)?;
+            } else if let Some((digit, index)) = self.set_cells.pop() {
+                self.set_cell(digit, index)?;
+            } else {
+                // Eliminate digits that cannot complete the sums of killer cages.
+                self.find_cage_eliminations()?;
+                if !self.del_maybes.is_empty() {
+                    continue;
+                }
+
+                // Eliminate digits out of order along thermometers.
+                self.find_thermo_eliminations()?;
+                if !self.del_maybes.is_empty() {
+                    continue;
+                }
+
+                // Attempt to detect any cells where it is the only possible option of a row/column/block,
+                // even if it itself has multiple possibilities.
+                if config.uses(Technique::HiddenSingles) {
+                    self.find_hidden_singles();
+                }
+                if !self.set_cells.is_empty() {
+                    // The same cell may be found through its row, column, and block, count it once.
+                    let mut found = [0u16; N];
+                    for (_, (x, y)) in self.set_cells.iter() {
+                        found[y] |= 1 << x;
+                    }
+                    stats.hidden_singles += found.iter().map(|row| row.count_ones() as usize).sum::<usize>();
+
+                    difficulty = difficulty.max(Difficulty::Easy);
+                    continue;
+                }
+
+                // Attempt to eliminate digits using pairs of cells that must hold the same two digits.
+                if config.uses(Technique::NakedPairs) {
+                    self.find_naked_pairs();
+                }
+                if !self.del_maybes.is_empty() {
+                    difficulty = difficulty.max(Difficulty::Medium);
+                    continue;
+                }
+
+                // Attempt to eliminate digits confined to the intersection of a block and a row or column.
+                if config.uses(Technique::PointingPairs) {
+                    self.find_pointing_pairs();
+                }
+                if !self.del_maybes.is_empty() {
+                    difficulty = difficulty.max(Difficulty::Medium);
+                    continue;
+                }
+
+                // Attempt to eliminate digits using three cells that must hold the same three digits.
+                if config.uses(Technique::NakedTriples) {
+                    self.find_naked_triples();
+                }
+                if !self.del_maybes.is_empty() {
+                    difficulty = difficulty.max(Difficulty::Medium);
+                    continue;
+                }
+
+                // Attempt to eliminate digits using two digits that must occupy the same two cells.
+                if config.uses(Technique::HiddenPairs) {
+                    self.find_hidden_pairs();
+                }
+                if !self.del_maybes.is_empty() {
+                    difficulty = difficulty.max(Difficulty::Medium);
+                    continue;
+                }
+
+                // Attempt to eliminate digits using three digits that must occupy the same three cells.
+                if config.uses(Technique::HiddenTriples) {
+                    self.find_hidden_triples();
+                }
+                if !self.del_maybes.is_empty() {
+                    difficulty = difficulty.max(Difficulty::Medium);
+                    continue;
+                }
+
+                // Attempt to eliminate digits using rectangles formed between two rows or columns.
+                if config.uses(Technique::XWing) {
+                    self.find_x_wing();
+                }
+                if !self.del_maybes.is_empty() {
+                    difficulty = difficulty.max(Difficulty::Hard);
+                    continue;
+                }
+
+                // Attempt to eliminate digits using four cells that must hold the same four digits.
+                if config.uses(Technique::NakedQuads) {
+                    self.find_naked_quads();
+                }
+                if !self.del_maybes.is_empty() {
+                    difficulty = difficulty.max(Difficulty::Hard);
+                    continue;
+                }
+
+                // Solver has exhausted its capabilities
+                #[cfg(test)]
+                self.check_invariants();
+                return Ok(difficulty);
+            }
+        }
+    }
+
+    fn apply_technique(&mut self, technique: Technique) {
+        //! Search for the deductions of a single technique, queueing them as pending operations.
+
+        match technique {
+            Technique::HiddenSingles => self.find_hidden_singles(),
+            Technique::NakedPairs => self.find_naked_pairs(),
+            Technique::PointingPairs => self.find_pointing_pairs(),
+            Technique::NakedTriples => self.find_naked_triples(),
+            Technique::HiddenPairs => self.find_hidden_pairs(),
+            Technique::HiddenTriples => self.find_hidden_triples(),
+            Technique::XWing => self.find_x_wing(),
+            Technique::NakedQuads => self.find_naked_quads(),
+        }
+    }
+
+    fn set_cell(&mut self, digit: usize, index: (usize, usize)) -> Result<(), SolveError> {
+        let contradiction = SolveError::Contradiction { x: index.0, y: index.1, digit: digit as u8 };
+
+        // Repeat check: check if already set
+        if self.cells[index.0][index.1] != 0 {
+            return if self.cells[index.0][index.1] != digit as u8 {
+                Err(contradiction) // Already set to something different, this is a contradiction.
+            } else {
+                Ok(()) // Already set to the correct value, no action necessary.
+            };
+        }
+
+        // Contradiction check: if attempt to set a cell that is not maybe the digit, return Err
+        if self.maybe[index.0][index.1] & 1 << (digit - 1) == 0 {
+            return Err(contradiction);
+        }
+
+
+        // Only the other cells of the row, column, and block where the digit is still possible are visited.
+        let mut xs = self.row_digit_cells[index.1][digit - 1] & !(1 << index.0);
+        while xs != 0 { // Remove maybes for each cell in row
+            let x = xs.trailing_zeros() as usize;
+            self.del_maybes.push((digit, (x, index.1)));
+            xs &= xs - 1;
+        }
+        let mut ys = self.col_digit_cells[index.0][digit - 1] & !(1 << index.1);
+        while ys != 0 { // Remove maybes for each cell in column
+            let y = ys.trailing_zeros() as usize;
+            self.del_maybes.push((digit, (index.0, y)));
+            ys &= ys - 1;
+        }
+
+        let blk = self.blocks[index.0][index.1] as usize;
+        let mut positions = self.blk_digit_cells[blk][digit - 1] & !(1 << self.block_positions[index.0][index.1]);
+        while positions != 0 { // Remove maybes for each cell in block
+            let position = positions.trailing_zeros() as usize;
+            self.del_maybes.push((digit, self.block_cells[blk][position]));
+            positions &= positions - 1;
+        }
+
+        let mut units = self.extra_units_of[index.0][index.1];
+        while units != 0 { // Remove maybes for each cell in the extra units through the cell, such as diagonals
+            let unit = units.trailing_zeros() as usize;
+            for (x, y) in self.extra_units[unit] {
+                if self.maybe[x][y] & 1 << (digit - 1) != 0 {
+                    self.del_maybes.push((digit, (x, y)));
+                }
+            }
+            units ^= 1 << unit;
+        }
+
+        if self.anti_knight { // Remove maybes for each cell a knight's move away
+            for (x, y) in Self::knight_moves(index) {
+                if self.maybe[x][y] & 1 << (digit - 1) != 0 {
+                    self.del_maybes.push((digit, (x, y)));
+                }
+            }
+        }
+
+        if self.cage_of[index.0][index.1] != 0 { // Remove maybes for each cell in the cage
+            for &(x, y) in &self.cages[self.cage_of[index.0][index.1] as usize - 1].cells {
+                if self.maybe[x][y] & 1 << (digit - 1) != 0 {
+                    self.del_maybes.push((digit, (x, y)));
+                }
+            }
+        }
+
+        // Set cell
+        self.cells[index.0][index.1] = digit as u8;
+        self.trail.push(Change::SetCell(index));
+
+        // Erase maybes, reporting any counter out of step with them against the digit being placed.
+        let mut maybes = self.maybe[index.0][index.1];
+        while maybes != 0 {
+            let di = maybes.trailing_zeros();
+            self.update_counters(di as usize + 1, index).map_err(|_| contradiction)?;
+            maybes ^= 1 << di;
+        }
+        self.maybe[index.0][index.1] = 0;
+        
+        Ok(())
+    }
+    fn del_maybe(&mut self, digit: usize, index: (usize, usize)) -> Result<(), SolveError> {
+        // If already unmaybed, return early
+        if self.maybe[index.0][index.1] & 1 << (digit - 1) == 0 {
+            return Ok(());
+        }
+
+        // Delete maybe
+        self.update_counters(digit, index)?;
+        self.maybe[index.0][index.1] &= !(1 << (digit - 1));
+
+        // If there are no remaining digits that may be set, this is a contradiction.
+        if self.maybe[index.0][index.1] == 0 {
+            return Err(SolveError::Contradiction { x: index.0, y: index.1, digit: digit as u8 });
+        }
+        // If there is only one remaining digit that may be set, set the cell.
+        if let Some(digit) = self.naked_single(index) {
+            self.set_cells.push((digit, index));
+        }
+
+        Ok(())
+    }
+    fn naked_single(&self, index: (usize, usize)) -> Option<usize> {
+        //! Returns the digit of an unset cell if it is the only one remaining possible there.
+
+        let maybes = self.maybe[index.0][index.1];
+        (maybes.count_ones() == 1).then(|| maybes.trailing_zeros() as usize + 1)
+    }
+    fn update_counters(&mut self, digit: usize, index: (usize, usize)) -> Result<(), SolveError> {
+        //! Decrement the row, column, and block counters according to the digit, clearing the cell from their
+        //! bitmaps, and record the removed maybe.
+        //! 
+        //! A counter that is already zero means the bookkeeping no longer matches the candidates, which is reported
+        //! as a contradiction at the cell before any counter is changed, rather than wrapping around.
+
+        let blk = self.blocks[index.0][index.1] as usize;
+        let mut underflow = self.row_digit_counters[index.1][digit - 1] == 0
+            || self.col_digit_counters[index.0][digit - 1] == 0
+            || self.blk_digit_counters[blk][digit - 1] == 0;
+        let mut units = self.extra_units_of[index.0][index.1];
+        while units != 0 {
+            let unit = units.trailing_zeros() as usize;
+            underflow |= self.extra_digit_counters[unit][digit - 1] == 0;
+            units ^= 1 << unit;
+        }
+        if underflow {
+            return Err(SolveError::Contradiction { x: index.0, y: index.1, digit: digit as u8 });
+        }
+
+        self.row_digit_counters[index.1][digit - 1] -= 1;
+        self.col_digit_counters[index.0][digit - 1] -= 1;
+        self.blk_digit_counters[blk    ][digit - 1] -= 1;
+        self.row_digit_cells[index.1][digit - 1] &= !(1 << index.0);
+        self.col_digit_cells[index.0][digit - 1] &= !(1 << index.1);
+        self.blk_digit_cells[blk    ][digit - 1] &= !(1 << self.block_positions[index.0][index.1]);
+        let mut units = self.extra_units_of[index.0][index.1];
+        while units != 0 {
+            let unit = units.trailing_zeros() as usize;
+            self.extra_digit_counters[unit][digit - 1] -= 1;
+            units ^= 1 << unit;
+        }
+
+        self.trail.push(Change::DelMaybe(digit, index));
+        Ok(())
+    }
+    fn undo(&mut self, checkpoint: usize) {
+        //! Revert all changes made since the trail was `checkpoint` long, discarding any pending operations.
+
+        while self.trail.len() > checkpoint {
+            match self.trail.pop().unwrap() {
+                Change::SetCell(index) => self.cells[index.0][index.1] = 0,
+                Change::DelMaybe(digit, index) => {
+                    self.maybe[index.0][index.1] |= 1 << (digit - 1);
+
+                    self.row_digit_counters[index.1                            ][digit - 1] += 1;
+                    self.col_digit_counters[index.0                            ][digit - 1] += 1;
+                    self.blk_digit_counters[self.blocks[index.0][index.1] as usize][digit - 1] += 1;
+                    self.row_digit_cells[index.1][digit - 1] |= 1 << index.0;
+                    self.col_digit_cells[index.0][digit - 1] |= 1 << index.1;
+                    self.blk_digit_cells[self.blocks[index.0][index.1] as usize][digit - 1]
+                        |= 1 << self.block_positions[index.0][index.1];
+                    let mut units = self.extra_units_of[index.0][index.1];
+                    while units != 0 {
+                        let unit = units.trailing_zeros() as usize;
+                        self.extra_digit_counters[unit][digit - 1] += 1;
+                        units ^= 1 << unit;
+                    }
+                }
+            }
+        }
+
+        self.del_maybes.clear();
+        self.set_cells.clear();
+
+        #[cfg(test)]
+        self.check_invariants();
+    }
+    #[cfg(test)]
+    fn check_invariants(&self) {
+        //! Panic if the digit counters and bitmaps do not match a recount of the maybes, or if a set cell still has
+        //! maybes.
+        //! This is only checked in tests, catching bookkeeping errors of the techniques early.
+
+        let mut row_digit_counters = [[0u8; N]; N];
+        let mut col_digit_counters = [[0u8; N]; N];
+        let mut blk_digit_counters = [[0u8; N]; N];
+        let mut row_digit_cells = [[0u16; N]; N];
+        let mut col_digit_cells = [[0u16; N]; N];
+        let mut blk_digit_cells = [[0u16; N]; N];
+        // Units added by constraints are indexed by the bits of `extra_units_of`.
+        let mut extra_digit_counters = [[0u8; N]; 16];
+        let extra_digit_counters = &mut extra_digit_counters[..self.extra_units.len()];
+
+        for y in 0..N {
+            for x in 0..N {
+                assert!(self.cells[x][y] == 0 || self.maybe[x][y] == 0,
+                    "set cell ({}, {}) has maybes {:#b}, this is likely a bug.", x, y, self.maybe[x][y]);
+
+                for di in (0..N).filter(|di| self.maybe[x][y] & 1 << di != 0) {
+                    row_digit_counters[y][di] += 1;
+                    col_digit_counters[x][di] += 1;
+                    blk_digit_counters[self.blocks[x][y] as usize][di] += 1;
+                    row_digit_cells[y][di] |= 1 << x;
+                    col_digit_cells[x][di] |= 1 << y;
+                    blk_digit_cells[self.blocks[x][y] as usize][di] |= 1 << self.block_positions[x][y];
+                    for (unit, counters) in extra_digit_counters.iter_mut().enumerate() {
+                        if self.extra_units_of[x][y] & 1 << unit != 0 {
+                            counters[di] += 1;
+                        }
+                    }
+                }
+            }
+        }
+
+        assert!(row_digit_counters == self.row_digit_counters
+            && col_digit_counters == self.col_digit_counters
+            && blk_digit_counters == self.blk_digit_counters
+            && *extra_digit_counters == self.extra_digit_counters[..],
+            "digit counters do not match the maybes, this is likely a bug.");
+        assert!(row_digit_cells == self.row_digit_cells
+            && col_digit_cells == self.col_digit_cells
+            && blk_digit_cells == self.blk_digit_cells,
+            "digit bitmaps do not match the maybes, this is likely a bug.");
+    }
+
+    fn unit_cells(&self, unit: usize) -> [(usize, usize); N] {
+        //! Returns the indecies of the cells of a unit: rows from 0 to N - 1, columns from N to 2N - 1,
+        //! blocks from 2N to 3N - 1, and the units added by constraints from 3N onwards, see `extra_units`.
+
+        if unit < N {
+            core::array::from_fn(|i| (i, unit))
+        } else if unit < 2 * N {
+            core::array::from_fn(|i| (unit - N, i))
+        } else if unit < 3 * N {
+            self.block_cells[unit - 2 * N]
+        } else {
+            self.extra_units[unit - 3 * N]
+        }
+    }
+    fn unit_kind(&self, unit: usize) -> UnitKind {
+        //! Returns the kind of a unit, indexed as by `unit_cells`.
+
+        match unit / N {
+            0 => UnitKind::Row,
+            1 => UnitKind::Col,
+            2 => UnitKind::Block,
+            _ if self.diagonals && unit < 3 * N + 2 => UnitKind::Diagonal,
+            _ => UnitKind::Window,
+        }
+    }
+    fn unit_count(&self) -> usize {
+        //! Returns the number of units constraining the grid, including those added by constraints.
+
+        3 * N + self.extra_units.len()
+    }
+    fn knight_moves(index: (usize, usize)) -> impl Iterator<Item = (usize, usize)> {
+        //! Returns the cells within the grid a knight's move away from the cell.
+
+        const MOVES: [(isize, isize); 8] = [(1, 2), (2, 1), (2, -1), (1, -2), (-1, -2), (-2, -1), (-2, 1), (-1, 2)];
+        MOVES.into_iter().filter_map(move |(dx, dy)| {
+            let (x, y) = (index.0.checked_add_signed(dx)?, index.1.checked_add_signed(dy)?);
+            (x < N && y < N).then_some((x, y))
+        })
+    }
+    fn knight_apart(a: (usize, usize), b: (usize, usize)) -> bool {
+        //! Check whether the cells are a knight's move apart.
+
+        let (dx, dy) = (a.0.abs_diff(b.0), a.1.abs_diff(b.1));
+        dx == 1 && dy == 2 || dx == 2 && dy == 1
+    }
+
+    fn find_cage_eliminations(&mut self) -> Result<(), SolveError> {
+        //! Eliminate the candidates of caged cells that no set of digits adding up to the cage's sum permits.
+
+        for cage in &self.cages {
+            // Set cells allow only their digit, unset cells allow their candidates.
+            let allowed = |(x, y): (usize, usize)| match self.cells[x][y] {
+                0 => self.maybe[x][y],
+                digit => 1 << (digit - 1),
+            };
+            let placed = cage.cells.iter().fold(0u16, |placed, &(x, y)| match self.cells[x][y] {
+                0 => placed,
+                digit => placed | 1 << (digit - 1),
+            });
+            let fits = cage.cells.iter().fold(0u16, |fits, &cell| fits | allowed(cell));
+
+            // Collect the digits of each cell that are part of a set that fits the cage.
+            let mut options = [0u16; N];
+            let mut possible = false;
+            for combo in cage.combos() {
+                if combo & placed != placed || combo & !fits != 0
+                || cage.cells.iter().any(|&cell| allowed(cell) & combo == 0) {
+                    continue;
+                }
+
+                possible = true;
+                for (i, &cell) in cage.cells.iter().enumerate() {
+                    options[i] |= allowed(cell) & combo;
+                }
+            }
+
+            if !possible {
+                // No set of digits fits the cage, all candidates of its cells are eliminated.
+                let (x, y) = cage.cells[0];
+                if cage.cells.iter().all(|&(x, y)| self.maybe[x][y] == 0) {
+                    return Err(SolveError::Contradiction { x, y, digit: self.cells[x][y] });
+                }
+                options = [0; N];
+            }
+
+            for (i, &(x, y)) in cage.cells.iter().enumerate() {
+                let mut maybes = self.maybe[x][y] & !options[i];
+                while maybes != 0 {
+                    let di = maybes.trailing_zeros();
+                    self.del_maybes.push((di as usize + 1, (x, y)));
+                    maybes ^= 1 << di;
+                }
+            }
+        }
+
+        Ok(())
+    }
+    fn find_thermo_eliminations(&mut self) -> Result<(), SolveError> {
+        //! Eliminate the candidates of cells on thermometers that are not above the lowest digit possible before them
+        //! or not below the highest digit possible after them.
+
+        for thermo in &self.thermos {
+            // Set cells allow only their digit, unset cells allow their candidates.
+            let allowed = |(x, y): (usize, usize)| match self.cells[x][y] {
+                0 => self.maybe[x][y],
+                digit => 1 << (digit - 1),
+            };
+
+            // Collect the digits of each cell above the lowest digit possible before it, then those below the highest
+            // digit possible after it. The bounds are digits, such that bits from `floor` are the digits above it.
+            let mut options = [0u16; N];
+            let mut floor = 0;
+            for (i, &cell) in thermo.cells.iter().enumerate() {
+                options[i] = allowed(cell) & !((1u32 << floor) - 1) as u16;
+                floor = if options[i] == 0 { N } else { options[i].trailing_zeros() as usize + 1 };
+            }
+            let mut ceiling = N + 1;
+            for (i, &(x, y)) in thermo.cells.iter().enumerate().rev() {
+                options[i] &= ((1u32 << (ceiling - 1)) - 1) as u16;
+                ceiling = if options[i] == 0 { 1 } else { 16 - options[i].leading_zeros() as usize };
+
+                // Set cells out of order are a contradiction, while unset cells lose all their candidates below.
+                if self.cells[x][y] != 0 && options[i] == 0 {
+                    return Err(SolveError::Contradiction { x, y, digit: self.cells[x][y] });
+                }
+            }
+
+            for (i, &(x, y)) in thermo.cells.iter().enumerate() {
+                let mut maybes = self.maybe[x][y] & !options[i];
+                while maybes != 0 {
+                    let di = maybes.trailing_zeros();
+                    self.del_maybes.push((di as usize + 1, (x, y)));
+                    maybes ^= 1 << di;
+                }
+            }
+        }
+
+        Ok(())
+    }
+    fn find_hidden_singles(&mut self) {
+        for row in 0..N {
+            self.push_hidden_singles((0..N).map(|x| (x, row)));
+        }
+        for col in 0..N {
+            self.push_hidden_singles((0..N).map(|y| (col, y)));
+        }
+        for blk in 0..N {
+            self.push_hidden_singles(self.block_cells[blk].into_iter());
+        }
+        for unit in 3 * N..self.unit_count() {
+            self.push_hidden_singles(self.unit_cells(unit).into_iter());
+        }
+    }
+    fn push_hidden_singles(&mut self, cells: impl Iterator<Item = (usize, usize)> + Clone) {
+        //! Push the digits possible in only one of the unit's cells to be set there.
+
+        // Accumulate the digits possible in at least one and in at least two of the cells.
+        let mut once = 0u16;
+        let mut twice = 0u16;
+        for (x, y) in cells.clone() {
+            twice |= once & self.maybe[x][y];
+            once |= self.maybe[x][y];
+        }
+
+        let singles = once & !twice;
+        if singles != 0 {
+            // hidden singles located, find and set
+            for (x, y) in cells {
+                let mut digits = self.maybe[x][y] & singles;
+                while digits != 0 {
+                    let di = digits.trailing_zeros();
+                    self.set_cells.push((di as usize + 1, (x, y)));
+                    digits ^= 1 << di;
+                }
+            }
+        }
+    }
+    fn find_naked_pairs(&mut self) {
+        for unit in 0..self.unit_count() {
+            let cells = self.unit_cells(unit);
+
+            for i in 0..N {
+                let pair = self.maybe[cells[i].0][cells[i].1];
+                if pair.count_ones() != 2 {
+                    continue;
+                }
+
+                for j in (i + 1)..N {
+                    if self.maybe[cells[j].0][cells[j].1] == pair {
+                        // naked pair located, eliminate its digits from the rest of the unit
+                        for k in 0..N {
+                            let (x, y) = cells[k];
+                            if k == i || k == j {
+                                continue;
+                            }
+
+                            let mut maybes = self.maybe[x][y] & pair;
+                            while maybes != 0 {
+                                let di = maybes.trailing_zeros();
+                                self.del_maybes.push((di as usize + 1, (x, y)));
+                                maybes ^= 1 << di;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    fn find_naked_triples(&mut self) {
+        self.find_naked_subsets(3);
+    }
+    fn find_naked_quads(&mut self) {
+        self.find_naked_subsets(4);
+    }
+    fn find_naked_subsets(&mut self, size: usize) {
+        //! Eliminate digits using `size` cells of a unit whose possibilities together span only `size` digits.
+
+        for unit in 0..self.unit_count() {
+            let cells = self.unit_cells(unit);
+
+            // Only unset cells with at most `size` possibilities can be part of the subset.
+            let mut members = [(0, 0u16); N];
+            let mut len = 0;
+            let mut unset = 0;
+            for i in 0..N {
+                let maybes = self.maybe[cells[i].0][cells[i].1];
+                if maybes.count_ones() >= 2 && maybes.count_ones() as usize <= size {
+                    members[len] = (i, maybes);
+                    len += 1;
+                }
+                if maybes != 0 {
+                    unset += 1;
+                }
+            }
+
+            // A subset can only eliminate digits if other cells of the unit remain unset.
+            if len < size || unset <= size {
+                continue;
+            }
+
+            let eliminates = |chosen: u16, union: u16| (0..N)
+                .any(|k| chosen & 1 << k == 0 && self.maybe[cells[k].0][cells[k].1] & union != 0);
+            if let Some((chosen, union)) = Self::subset(&members[..len], size, 0, 0, &eliminates) {
+                // naked subset located, eliminate its digits from the rest of the unit
+                for k in 0..N {
+                    let (x, y) = cells[k];
+                    if chosen & 1 << k != 0 {
+                        continue;
+                    }
+
+                    let mut maybes = self.maybe[x][y] & union;
+                    while maybes != 0 {
+                        let di = maybes.trailing_zeros();
+                        self.del_maybes.push((di as usize + 1, (x, y)));
+                        maybes ^= 1 << di;
+                    }
+                }
+            }
+        }
+    }
+    fn find_hidden_pairs(&mut self) {
+        self.find_hidden_subsets(2);
+    }
+    fn find_hidden_triples(&mut self) {
+        self.find_hidden_subsets(3);
+    }
+    fn find_hidden_subsets(&mut self, size: usize) {
+        //! Eliminate the other digits from `size` cells of a unit that are the only cells where `size` digits are possible.
+
+        for unit in 0..self.unit_count() {
+            let cells = self.unit_cells(unit);
+            let counters = match unit {
+                unit if unit < N => self.row_digit_counters[unit],
+                unit if unit < 2 * N => self.col_digit_counters[unit - N],
+                unit if unit < 3 * N => self.blk_digit_counters[unit - 2 * N],
+                unit => self.extra_digit_counters[unit - 3 * N],
+            };
+
+            // Only digits possible in at most `size` cells of the unit can be part of the subset.
+            let mut members = [(0, 0u16); N];
+            let mut len = 0;
+            let mut unplaced = 0;
+            for di in 0..N {
+                let count = counters[di] as usize;
+                if count >= 2 && count <= size {
+                    let mut positions = 0u16;
+                    for i in 0..N {
+                        if self.maybe[cells[i].0][cells[i].1] & 1 << di != 0 {
+                            positions |= 1 << i;
+                        }
+                    }
+                    members[len] = (di, positions);
+                    len += 1;
+                }
+                if count != 0 {
+                    unplaced += 1;
+                }
+            }
+
+            // A subset can only eliminate digits if other digits of the unit remain to be placed.
+            if len < size || unplaced <= size {
+                continue;
+            }
+
+            let eliminates = |digits: u16, positions: u16| (0..N)
+                .any(|i| positions & 1 << i != 0 && self.maybe[cells[i].0][cells[i].1] & !digits != 0);
+            if let Some((digits, positions)) = Self::subset(&members[..len], size, 0, 0, &eliminates) {
+                // hidden subset located, eliminate all other digits from its cells
+                for i in 0..N {
+                    if positions & 1 << i == 0 {
+                        continue;
+                    }
+
+                    let (x, y) = cells[i];
+                    let mut maybes = self.maybe[x][y] & !digits;
+                    while maybes != 0 {
+                        let di = maybes.trailing_zeros();
+                        self.del_maybes.push((di as usize + 1, (x, y)));
+                        maybes ^= 1 << di;
+                    }
+                }
+            }
+        }
+    }
+    fn subset(
+        members: &[(usize, u16)],
+        size: usize,
+        chosen: u16,
+        union: u16,
+        accept: &impl Fn(u16, u16) -> bool,
+    ) -> Option<(u16, u16)> {
+        //! Extend the chosen members, as a bitmap of their indices, such that the union of their bitmaps stays within
+        //! `size` bits. Returns the chosen members and their union once `size` are chosen, if `accept`ed.
+
+        if chosen.count_ones() as usize == size {
+            return if union.count_ones() as usize == size && accept(chosen, union) { Some((chosen, union)) } else { None };
+        }
+
+        for m in 0..members.len() {
+            let (i, bits) = members[m];
+            if (union | bits).count_ones() as usize <= size {
+                let subset = Self::subset(&members[m + 1..], size, chosen | 1 << i, union | bits, accept);
+                if subset.is_some() {
+                    return subset;
+                }
+            }
+        }
+        None
+    }
+    fn find_pointing_pairs(&mut self) {
+        for blk in 0..N {
+            let blk_cells = self.unit_cells(2 * N + blk);
+
+            for di in 0..N {
+                if self.blk_digit_counters[blk][di] < 2 {
+                    continue;
+                }
+
+                // Collect the rows and columns holding the block's possibilities for the digit.
+                let mut rows = 0u16;
+                let mut cols = 0u16;
+                for (x, y) in blk_cells {
+                    if self.maybe[x][y] & 1 << di != 0 {
+                        rows |= 1 << y;
+                        cols |= 1 << x;
+                    }
+                }
+
+                // Possibilities confined to a single row or column of the block eliminate the digit
+                // from the rest of that row or column.
+                if rows.count_ones() == 1 {
+                    self.del_maybes_outside(di, self.unit_cells(rows.trailing_zeros() as usize), 2 * N + blk);
+                }
+                if cols.count_ones() == 1 {
+                    self.del_maybes_outside(di, self.unit_cells(N + cols.trailing_zeros() as usize), 2 * N + blk);
+                }
+            }
+        }
+
+        for line in 0..2 * N {
+            let line_cells = self.unit_cells(line);
+
+            for di in 0..N {
+                let counter = if line < N {
+                    self.row_digit_counters[line][di]
+                } else {
+                    self.col_digit_counters[line - N][di]
+                };
+                if counter < 2 {
+                    continue;
+                }
+
+                // Collect the blocks holding the row or column's possibilities for the digit.
+                let mut blks = 0u16;
+                for (x, y) in line_cells {
+                    if self.maybe[x][y] & 1 << di != 0 {
+                        blks |= 1 << self.blocks[x][y];
+                    }
+                }
+
+                // Possibilities confined to a single block eliminate the digit from the rest of the block.
+                if blks.count_ones() == 1 {
+                    self.del_maybes_outside(di, self.unit_cells(2 * N + blks.trailing_zeros() as usize), line);
+                }
+            }
+        }
+    }
+    fn del_maybes_outside(&mut self, di: usize, cells: [(usize, usize); N], unit: usize) {
+        //! Push deletions of the digit index from all of the cells that are not also part of `unit`.
+
+        let unit_cells = self.unit_cells(unit);
+        for (x, y) in cells {
+            if self.maybe[x][y] & 1 << di != 0 && !unit_cells.contains(&(x, y)) {
+                self.del_maybes.push((di + 1, (x, y)));
+            }
+        }
+    }
+    fn find_x_wing(&mut self) {
+        for di in 0..N {
+            // Bitmaps of the columns where the digit is possible per row, and of the rows where it is possible per column.
+            let mut row_cols = [0u16; N];
+            let mut col_rows = [0u16; N];
+            for y in 0..N {
+                for x in 0..N {
+                    if self.maybe[x][y] & 1 << di != 0 {
+                        row_cols[y] |= 1 << x;
+                        col_rows[x] |= 1 << y;
+                    }
+                }
+            }
+
+            // Rows confined to the same two columns eliminate from those columns, and vice versa.
+            for (lines, cross_offset) in [(row_cols, N), (col_rows, 0)] {
+                for a in 0..N {
+                    if lines[a].count_ones() != 2 {
+                        continue;
+                    }
+
+                    for b in (a + 1)..N {
+                        if lines[b] == lines[a] {
+                            // x-wing located, eliminate the digit from the crossing lines outside of the rectangle
+                            let mut crosses = lines[a];
+                            while crosses != 0 {
+                                let cross = crosses.trailing_zeros() as usize;
+                                for (i, (x, y)) in self.unit_cells(cross_offset + cross).into_iter().enumerate() {
+                                    if i != a && i != b && self.maybe[x][y] & 1 << di != 0 {
+                                        self.del_maybes.push((di + 1, (x, y)));
+                                    }
+                                }
+                                crosses ^= 1 << cross;
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    fn choose_guess_cell(&self) -> Option<(usize, usize)> {
+        //! Search the grid for the unsolved cell with the fewest possibilities, if any.
+        //! Ties go to the first cell in row-major order, which `solve` documents as stable.
+
+        let mut index = None;
+        let mut fewest = u32::MAX;
+        for y in 0..N {
+            for x in 0..N {
+                let count = self.maybe[x][y].count_ones();
+                if count != 0 && count < fewest {
+                    index = Some((x, y));
+                    fewest = count;
+                }
+            }
+        }
+        index
+    }
+    fn next_guess(&mut self, guesses: &mut Guesses<N>) -> bool {
+        //! Undo the most recent guess and set its cell to the next untried possibility,
+        //! discarding guesses with none remaining. Returns false if no guesses remain.
+
+        while let Some((checkpoint, index, maybes)) = guesses.pop() {
+            self.undo(checkpoint);
+
+            if maybes != 0 {
+                let digit = maybes.trailing_zeros() as usize + 1;
+                guesses.push((checkpoint, index, maybes & (maybes - 1)));
+                self.set_cells.push((digit, index));
+                return true;
+            }
+        }
+
+        false
+    }
+}
+
+impl Grid {
+    /// Serialize grid into 41 bytes, packing the digit of each cell into a nibble in the same order as `to_str`.
+    /// 
+    /// The first cell of each pair occupies the high nibble, the low nibble of the last byte is padding.
+    pub fn to_bytes(&self) -> [u8; 41] {
+        let mut bytes = [0u8; 41];
+        let board = self.board();
+
+        for i in 0..81 {
+            let digit = board[i % 9][i / 9];
+            bytes[i / 2] |= if i % 2 == 0 { digit << 4 } else { digit };
+        }
+
+        bytes
+    }
+    /// Parses 41 bytes of packed digits as produced by `to_bytes`, returning an error if they are invalid.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ParseError> {
+        if bytes.len() != 41 {
+            return Err(ParseError::InvalidLength(bytes.len()));
+        }
+
+        let mut cells = [[0u8; 9]; 9];
+        for i in 0..81 {
+            cells[i % 9][i / 9] = if i % 2 == 0 { bytes[i / 2] >> 4 } else { bytes[i / 2] & 0xf };
+        }
+
+        Grid::from_cells(cells)
+    }
+
+    /// Returns the equivalent grid that is lexicographically smallest by its `to_str` form, with blanks first.
+    /// 
+    /// Grids are equivalent if one can be turned into the other by relabeling digits, permuting rows within
+    /// bands or the bands themselves, permuting columns within stacks or the stacks themselves, and transposing,
+    /// which together include all rotations and reflections. Equivalent grids thus share a canonical form,
+    /// such that collections of puzzles can be deduplicated with it. Constraints are not carried over.
+    pub fn canonical(&self) -> Grid {
+        const PERMS: [[usize; 3]; 6] = [[0, 1, 2], [0, 2, 1], [1, 0, 2], [1, 2, 0], [2, 0, 1], [2, 1, 0]];
+
+        let board = self.board();
+        let mut best = None;
+
+        for transposed in [false, true] {
+            // Rows of the grid, indexed by rows then by columns.
+            let mut rows = [[0u8; 9]; 9];
+            for y in 0..9 {
+                for x in 0..9 {
+                    rows[y][x] = if transposed { board[y][x] } else { board[x][y] };
+                }
+            }
+
+            // Try each order of the columns, leaving the order of the rows to be searched for.
+            for stacks in PERMS {
+                for a in PERMS {
+                    for b in PERMS {
+                        for c in PERMS {
+                            let mut permuted = [[0u8; 9]; 9];
+                            for (i, within) in [a, b, c].into_iter().enumerate() {
+                                for j in 0..3 {
+                                    for row in 0..9 {
+                                        permuted[row][i * 3 + j] = rows[row][stacks[i] * 3 + within[j]];
+                                    }
+                                }
+                            }
+
+                            let mut current = [[0u8; 9]; 9];
+                            Grid::canonical_rows(&permuted, 0, 0, [0; 10], &mut current, true, &mut best);
+                        }
+                    }
+                }
+            }
+        }
+
+        let best = best.unwrap();
+        let mut cells = [[0u8; 9]; 9];
+        for y in 0..9 {
+            for x in 0..9 {
+                cells[x][y] = best[y][x];
+            }
+        }
+        Grid::from_cells(cells).unwrap()
+    }
+    /// Check whether the grids are equivalent, such that one can be turned into the other as described by `canonical`.
+    pub fn is_equivalent(&self, other: &Grid) -> bool {
+        // Equivalent grids have as many digits set, which is much quicker to compare than their canonical forms.
+        let filled = |grid: &Grid| grid.board().iter().flatten().filter(|&&digit| digit != 0).count();
+        filled(self) == filled(other) && self.canonical() == other.canonical()
+    }
+    fn canonical_rows(
+        rows: &[[u8; 9]; 9],
+        depth: usize,
+        used: u16,
+        labels: [u8; 10],
+        current: &mut [[u8; 9]; 9],
+        bounded: bool,
+        best: &mut Option<[[u8; 9]; 9]>,
+    ) {
+        //! Search the orders of the rows for the smallest relabeled grid, filling `current` from `depth` onwards.
+        //! `labels` maps each digit to its new label, or zero if yet to be labeled, and `used` holds the rows taken.
+        //! While `bounded`, the rows so far equal those of `best`, which are replaced should a smaller grid be found.
+
+        if depth == 9 {
+            if !bounded {
+                *best = Some(*current);
+            }
+            return;
+        }
+
+        // A new band may be started at the start of each band, otherwise the partially used band must be completed.
+        let band = (0..3).find(|&band| (used >> (band * 3) & 0b111).count_ones() as usize == depth % 3);
+        let candidates = (0..9).filter(|&row| used & 1 << row == 0 && match depth % 3 {
+            0 => used & 0b111 << (row / 3 * 3) == 0,
+            _ => Some(row / 3) == band,
+        });
+
+        // Relabel each candidate row, digits not yet labeled are labeled in the order they appear.
+        let mut relabeled = [([0u8; 9], [0u8; 10]); 9];
+        let mut smallest = None;
+        for row in candidates.clone() {
+            let mut labels = labels;
+            let mut next = labels.iter().max().unwrap() + 1;
+            for col in 0..9 {
+                let digit = rows[row][col] as usize;
+                if digit != 0 && labels[digit] == 0 {
+                    labels[digit] = next;
+                    next += 1;
+                }
+                relabeled[row].0[col] = labels[digit];
+            }
+            relabeled[row].1 = labels;
+
+            if smallest.is_none_or(|smallest| relabeled[row].0 < smallest) {
+                smallest = Some(relabeled[row].0);
+            }
+        }
+        let smallest = smallest.unwrap();
+
+        let mut bounded = bounded && best.is_some();
+        if bounded {
+            let bound = best.unwrap()[depth];
+            if smallest > bound {
+                return;
+            }
+            bounded = smallest == bound;
+        }
+
+        current[depth] = smallest;
+        for row in candidates {
+            if relabeled[row].0 == smallest {
+                Grid::canonical_rows(rows, depth + 1, used | 1 << row, relabeled[row].1, current, bounded, best);
+                // A smaller grid may have been found, which the remaining candidates must be bounded by.
+                bounded = best.is_some_and(|best| best[..=depth] == current[..=depth]);
+            }
+        }
+    }
+}
+
+/// Parses and solves a grid string, returning the solution as an 81-character string, or a description of the error.
+/// 
+/// Either grid string format is accepted, see `Grid::from_str`. This never panics, such that it may be exposed
+/// directly to other environments, such as through WebAssembly.
+#[cfg(feature = "alloc")]
+pub fn solve_str(input: &str) -> Result<String, String> {
+    let mut grid = input.parse::<Grid>().map_err(|err| err.to_string())?;
+    grid.solve().map_err(|err| err.to_string())?;
+    Ok(grid.to_str())
+}
+
+/// Solves each line of `reader` as a puzzle in turn, writing a line per solution to `writer` as it goes.
+/// 
+/// Lines that cannot be parsed or solved are written as `error: ` followed by the description of the error,
+/// without stopping the run. Blank lines are skipped. A single grid is reused throughout, such that puzzle files
+/// of any size are processed without being loaded into memory. Returns the number of puzzles solved and failed,
+/// or the first error reading from `reader` or writing to `writer`.
+#[cfg(feature = "std")]
+pub fn solve_reader<R: std::io::BufRead, W: std::io::Write>(mut reader: R, mut writer: W) -> std::io::Result<(usize, usize)> {
+    let mut grid = Grid::default();
+    let mut line = Vec::new();
+    let (mut solved, mut failed) = (0, 0);
+    loop {
+        line.clear();
+        if reader.read_until(b'\n', &mut line)? == 0 {
+            break;
+        }
+        // Invalid UTF-8 is replaced, such that it is reported as an unexpected character of the line.
+        let line = String::from_utf8_lossy(&line);
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let result = grid.load_str(&line).map_err(|err| err.to_string())
+            .and_then(|()| grid.solve().map_err(|err| err.to_string()));
+        match result {
+            Ok(()) => {
+                writeln!(writer, "{}", grid.to_str())?;
+                solved += 1;
+            },
+            Err(err) => {
+                writeln!(writer, "error: {}", err)?;
+                failed += 1;
+            },
+        }
+    }
+
+    Ok((solved, failed))
+}
+
+/// A small random number generator, where a consistent sequence of random numbers is wanted without one being given.
+struct SplitMix64(u64);
+
+impl RngCore for SplitMix64 {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        rand_core::impls::fill_bytes_via_next(self, dst)
+    }
+}
+
+fn shuffle<T>(slice: &mut [T], rng: &mut impl RngCore) {
+    //! Shuffle the slice in place using the Fisher-Yates algorithm.
+
+    for i in (1..slice.len()).rev() {
+        slice.swap(i, rng.next_u32() as usize % (i + 1));
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
 mod tests {
+    use alloc::format;
     use alloc::string::ToString;
+    use alloc::vec::Vec;
+    use rand_core::SeedableRng;
+
+    #[test]
+    fn test_solver() {
+        let mut grid = super::Grid::from_str("600008940900006100070040000200610000000000200089002000000060005000000030800001600".to_string());
+        assert!(grid.solve().is_ok());
+        assert_eq!(grid.to_str(), "625178943948326157371945862257619384463587291189432576792863415516294738834751629");
+
+        let mut grid2 = super::Grid::from_str("100007090030020008009600500005300900010080002600004000300000010040000007007000300".to_string());
+        assert!(grid2.solve().is_ok());
+        assert_eq!(grid2.to_str(), "162857493534129678789643521475312986913586742628794135356478219241935867897261354");
+
+        
+        let mut grid3 = super::Grid::from_str("234500200000023040000030400000600000300000000000230040040000654300000010203000004".to_string());
+        assert!(grid3.solve().is_err());
+    }
+
+    #[test]
+    fn test_from_cells() {
+        let str = "600008940900006100070040000200610000000000200089002000000060005000000030800001600";
+        let mut cells = [[0u8; 9]; 9];
+        for (i, char) in str.chars().enumerate() {
+            cells[i % 9][i / 9] = char.to_digit(10).unwrap() as u8;
+        }
+
+        let grid = super::Grid::from_cells(cells).unwrap();
+        assert_eq!(format!("{:?}", grid), format!("{:?}", super::Grid::from_str(str.to_string())));
+
+        cells[4][7] = 10;
+        assert_eq!(super::Grid::from_cells(cells).unwrap_err(), super::ParseError::InvalidDigit { x: 4, y: 7, value: 10 });
+    }
+
+    #[test]
+    fn test_get() {
+        let mut grid = super::Grid::from_str("600008940900006100070040000200610000000000200089002000000060005000000030800001600".to_string());
+        assert_eq!(grid.get(0, 0), 6);
+        assert_eq!(grid.get(3, 1), 0);
+        assert!(grid.solve().is_ok());
+        assert_eq!(grid.get(0, 0), 6);
+        assert_eq!(grid.get(3, 1), 3);
+        assert_eq!(grid.get_checked(8, 8), Some(9));
+        assert_eq!(grid.get_checked(9, 0), None);
+        assert_eq!(grid.get_checked(0, 9), None);
+    }
+
+    #[test]
+    fn test_candidates() {
+        let mut grid = super::Grid::from_str("600008940900006100070040000200610000000000200089002000000060005000000030800001600".to_string());
+        // The givens are eliminated from their peers before the grid is solved, leaving 1, 2, 3, and 5.
+        assert_eq!(grid.maybe[1][0], 0x1ff);
+        assert_eq!(grid.candidates(1, 0), 0b10111);
+        assert!(grid.solve().is_ok());
+        assert_eq!(grid.candidates(1, 0), 0);
+
+        let mut grid = super::Grid::from_str("000000000000000000000000000000000000000000000000000000000000000000000000123456780".to_string());
+        while let Some((digit, index)) = grid.set_cells.pop() {
+            grid.set_cell(digit, index).unwrap();
+            while let Some((digit, index)) = grid.del_maybes.pop() {
+                grid.del_maybe(digit, index).unwrap();
+            }
+        }
+        assert_eq!(grid.get(8, 8), 9);
+        assert_eq!(grid.candidates(8, 0), 0x0ff);
+        assert_eq!(grid.candidate_digits(8, 0).collect::<Vec<_>>(), [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(grid.candidate_digits(0, 0).collect::<Vec<_>>(), [2, 3, 4, 5, 6, 7, 8, 9]);
+    }
+
+    #[test]
+    fn test_display() {
+        let mut grid = super::Grid::from_str("625178943948326157371945862257619384463587291189432576792863415516294738834751629".to_string());
+        grid.solve().unwrap();
+        grid.cells[1][0] = 0;
+        assert_eq!(format!("{}", grid), "\
++---+---+---+
+|6.5|178|943|
+|948|326|157|
+|371|945|862|
++---+---+---+
+|257|619|384|
+|463|587|291|
+|189|432|576|
++---+---+---+
+|792|863|415|
+|516|294|738|
+|834|751|629|
++---+---+---+");
+    }
+
+    #[test]
+    fn test_count_solutions() {
+        let grid = super::Grid::from_str("600008940900006100070040000200610000000000200089002000000060005000000030800001600".to_string());
+        assert_eq!(grid.count_solutions(2), 1);
+        assert_eq!(grid.count_solutions(0), 0);
+        assert_eq!(grid.to_str(), "600008940900006100070040000200610000000000200089002000000060005000000030800001600");
+
+        let grid = super::Grid::from_str("625178943048026157071045862257619384463587291189432576792863415516294738834751629".to_string());
+        assert_eq!(grid.count_solutions(1), 1);
+        assert_eq!(grid.count_solutions(2), 2);
+        assert_eq!(grid.count_solutions(10), 2);
+
+        let grid = super::Grid::from_str("234500200000023040000030400000600000300000000000230040040000654300000010203000004".to_string());
+        assert_eq!(grid.count_solutions(2), 0);
+    }
+
+    #[test]
+    fn test_has_unique_solution() {
+        let grid = super::Grid::from_str("600008940900006100070040000200610000000000200089002000000060005000000030800001600".to_string());
+        assert!(grid.has_unique_solution());
+
+        let grid = super::Grid::from_str("625178943948326157371945862257619384463587291189432576792863415516294738834751629".to_string());
+        assert!(grid.has_unique_solution());
+
+        let grid = super::Grid::from_str("625178943048026157071045862257619384463587291189432576792863415516294738834751629".to_string());
+        assert!(!grid.has_unique_solution());
+
+        let grid = super::Grid::from_str("234500200000023040000030400000600000300000000000230040040000654300000010203000004".to_string());
+        assert!(!grid.has_unique_solution());
+    }
+
+    #[test]
+    fn test_generate() {
+        let mut rng = rand_pcg::Pcg32::seed_from_u64(0);
+
+        for clues in [30, 25, 0] {
+            let grid = super::Grid::generate(clues, &mut rng);
+            let count = grid.clue_count();
+            assert!(count >= clues && count >= 17);
+            assert!(clues < 25 || count == clues);
+            assert!(grid.has_unique_solution());
+
+            let mut solution = grid.clone();
+            assert!(solution.solve().is_ok());
+            assert!(solution.verify_solution());
+        }
+    }
+
+    #[test]
+    fn test_difficulty() {
+        use super::Difficulty;
+
+        let grid = super::Grid::from_str("350008100000000000600507490018095000040001006502003980000800007070060000080704023".to_string());
+        assert_eq!(grid.difficulty(), Difficulty::Trivial);
+
+        let grid = super::Grid::from_str("000500000630000001000263749060900530000075000003008900040030000002050060500000010".to_string());
+        assert_eq!(grid.difficulty(), Difficulty::Easy);
+        assert!(!grid.requires_guessing());
+
+        let grid = super::Grid::from_str("800000320007000600009274800000705006050040008600000000000916000000500000100000035".to_string());
+        assert_eq!(grid.difficulty(), Difficulty::Guessing);
+        assert!(grid.requires_guessing());
+        assert!(!grid.solved().unwrap().requires_guessing());
+
+        let grid = super::Grid::from_str("234500200000023040000030400000600000300000000000230040040000654300000010203000004".to_string());
+        assert_eq!(grid.difficulty(), Difficulty::Guessing);
+        assert!(!grid.requires_guessing());
+        assert!(super::Grid::default().requires_guessing());
+    }
+
+    #[test]
+    fn test_steps() {
+        use super::Step;
+
+        let puzzle = "000500000630000001000263749060900530000075000003008900040030000002050060500000010";
+        let mut grid = super::Grid::from_str(puzzle.to_string());
+        assert_eq!(grid.next_step(), Some(Step::Placement { digit: 1, x: 7, y: 8 }));
+
+        let (mut hidden_singles, mut naked_singles) = (0, 0);
+        while let Some(step) = grid.next_step() {
+            match step {
+                Step::HiddenSingle { .. } => hidden_singles += 1,
+                Step::NakedSingle { digit, x, y } => {
+                    assert_eq!(grid.maybe[x][y], 1 << (digit - 1));
+                    naked_singles += 1;
+                },
+                _ => (),
+            }
+            assert!(grid.apply_step(step).is_ok());
+        }
+        assert!(hidden_singles > 0 && naked_singles > 0);
+
+        let mut solution = super::Grid::from_str(puzzle.to_string());
+        assert!(solution.solve().is_ok());
+        assert_eq!(grid.to_str(), solution.to_str());
+    }
+
+    #[test]
+    fn test_solve_with() {
+        use super::{SolveError, SolverConfig, Technique};
+
+        let puzzle = "019700006000030070060200000300000601400000080000007002290068000040050000000000040";
+        let mut grid = super::Grid::from_str(puzzle.to_string());
+        assert!(grid.solve_with(&SolverConfig::default()).is_ok());
+        assert!(grid.verify_solution());
+
+        // The x-wing is required to complete the puzzle without guessing.
+        let config = SolverConfig::new().technique(Technique::XWing, false).guessing(false);
+        assert!(!config.uses(Technique::XWing) && config.uses(Technique::NakedPairs) && !config.allows_guessing());
+        let mut grid = super::Grid::from_str(puzzle.to_string());
+        assert_eq!(grid.solve_with(&config), Err(SolveError::Stuck));
+        assert!(grid.clue_count() < grid.board().iter().flatten().filter(|&&digit| digit != 0).count());
+        assert!(grid.solve_with(&config.technique(Technique::XWing, true)).is_ok());
+        assert!(grid.verify_solution());
+
+        let mut grid = super::Grid::from_str(puzzle.to_string());
+        assert!(grid.solve_with(&SolverConfig::new().without_techniques()).is_ok());
+        assert!(grid.verify_solution());
+    }
+
+    #[test]
+    fn test_techniques() {
+        use super::{Difficulty, SolveError, SolveStats, SolverConfig, Technique};
+
+        // The other techniques get stuck on each puzzle, at which point the technique makes exactly these
+        // eliminations, as `(digit, (x, y))`, and goes on to complete the puzzle without guessing.
+        let cases: [(Technique, &str, Difficulty, &[super::stack::Op]); 7] = [
+            (Technique::NakedPairs, "019600000240000090000080070508009300700000120300007000002010003000008200100000008",
+                Difficulty::Medium, &[(4, (3, 7)), (4, (4, 7)), (9, (4, 7))]),
+            (Technique::PointingPairs, "000070400000800069180000000000910380010004020000020050020409003043000000050600002",
+                Difficulty::Medium, &[(3, (3, 0)), (3, (3, 2)), (7, (6, 1))]),
+            (Technique::NakedTriples, "000000074003900005200051600300040000006803000010009500039006000400080000008005000",
+                Difficulty::Medium, &[(1, (6, 6)), (1, (6, 7)), (2, (6, 4)), (2, (6, 6)), (2, (6, 7)), (8, (6, 6))]),
+            (Technique::HiddenPairs, "000400002600000000003150800094000000000390050070006000400020001705000030102500400",
+                Difficulty::Medium, &[(7, (5, 4)), (8, (5, 4))]),
+            (Technique::HiddenTriples, "010000800000608050000900020000410037300079000170803000009000000000000049065200000",
+                Difficulty::Medium, &[(2, (0, 0)), (4, (0, 0)), (4, (0, 2)), (4, (1, 2)), (9, (0, 0))]),
+            (Technique::XWing, "019700006000030070060200000300000601400000080000007002290068000040050000000000040",
+                Difficulty::Hard, &[(9, (3, 3)), (9, (5, 2)), (9, (5, 3)), (9, (6, 2))]),
+            (Technique::NakedQuads, "080000400009200010030001500000000630050308009004609000000126304040000200500800001",
+                Difficulty::Hard, &[(4, (4, 1)), (4, (4, 2)), (5, (4, 1)), (5, (4, 7)), (7, (4, 0)), (7, (4, 1)), (7, (4, 2))]),
+        ];
+
+        for (technique, puzzle, difficulty, eliminations) in cases {
+            let grid = super::Grid::from_str(puzzle.to_string());
+            assert_eq!(grid.difficulty(), difficulty, "{:?}", technique);
+
+            let config = SolverConfig::new().technique(technique, false).guessing(false);
+            assert_eq!(grid.clone().solve_with(&config), Err(SolveError::Stuck), "{:?}", technique);
+
+            let mut stuck = grid.clone();
+            assert!(stuck.propagate_with(&config, &mut SolveStats::default()).is_ok());
+            stuck.apply_technique(technique);
+            let mut found = stuck.del_maybes.iter().collect::<Vec<_>>();
+            found.sort_unstable();
+            found.dedup();
+            assert_eq!(found, eliminations, "{:?}", technique);
+
+            let mut solved = grid.clone();
+            assert!(solved.solve_with(&config.technique(technique, true)).is_ok(), "{:?}", technique);
+            assert!(solved.verify_solution());
+        }
+    }
+
+    #[test]
+    fn test_x_wing() {
+        // The other logical techniques get stuck on this puzzle, an x-wing is required to proceed without guessing.
+        let mut grid = super::Grid::from_str("019700006000030070060200000300000601400000080000007002290068000040050000000000040".to_string());
+        assert_eq!(grid.difficulty(), super::Difficulty::Hard);
+        assert!(grid.propagate().is_ok());
+        assert!(grid.verify_solution());
+    }
+
+    #[test]
+    fn test_no_maybe_pair() {
+        // No digit in the empty grid is limited to a pair of cells, the solver must guess some other way.
+        let mut grid = super::Grid::default();
+        assert!(grid.solve().is_ok());
+        assert!(grid.verify_solution());
+
+        assert_eq!(super::Grid::default().count_solutions(3), 3);
+    }
+
+    #[test]
+    fn test_solutions() {
+        let grid = super::Grid::from_str("625178943048026157071045862257619384463587291189432576792863415516294738834751629".to_string());
+        let solutions = grid.solutions().map(|solution| solution.to_str()).collect::<Vec<_>>();
+        assert_eq!(solutions.len(), 2);
+        assert!(solutions.contains(&"625178943948326157371945862257619384463587291189432576792863415516294738834751629".to_string()));
+        assert!(solutions.contains(&"625178943348926157971345862257619384463587291189432576792863415516294738834751629".to_string()));
+        assert_eq!(grid.solve_all(5).iter().map(|solution| solution.to_str()).collect::<Vec<_>>(), solutions);
+        assert_eq!(grid.solve_all(1).len(), 1);
+        assert!(grid.solve_all(0).is_empty());
+
+        let mut solutions = super::Grid::default().solutions();
+        assert!(solutions.next().unwrap().verify_solution());
+        assert!(solutions.next().unwrap().verify_solution());
+
+        let grid = super::Grid::from_str("234500200000023040000030400000600000300000000000230040040000654300000010203000004".to_string());
+        assert!(grid.solutions().next().is_none());
+        assert!(grid.solve_all(5).is_empty());
+
+        let solutions = super::Grid::default().solve_all(5);
+        assert_eq!(solutions.len(), 5);
+        assert!(solutions.iter().all(|solution| solution.verify_solution()));
+        assert!((1..5).all(|i| !solutions[..i].contains(&solutions[i])));
+    }
+
+    #[test]
+    fn test_try_from_str() {
+        use super::{Grid, ParseError};
+
+        assert!(Grid::try_from_str(" 600008940900006100070040000200610000000000200089002000000060005000000030800001600\n").is_ok());
+        assert_eq!(Grid::try_from_str("60000894090000610007").unwrap_err(), ParseError::InvalidLength(20));
+        assert_eq!(Grid::try_from_str("60000894090000610007004000020061000000000020008900200000006000500000003080000160x").unwrap_err(), ParseError::InvalidCharacter('x'));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let puzzle = "600008940900006100070040000200610000000000200089002000000060005000000030800001600";
+        let grid = super::Grid::from_str(puzzle.to_string());
+
+        let json = serde_json::to_string(&grid).unwrap();
+        assert_eq!(json, format!("\"{}\"", puzzle));
+        let mut grid = serde_json::from_str::<super::Grid>(&json).unwrap();
+        assert!(grid.solve().is_ok());
+        assert_eq!(serde_json::to_string(&grid).unwrap(), "\"625178943948326157371945862257619384463587291189432576792863415516294738834751629\"");
+
+        assert!(serde_json::from_str::<super::Grid>("\"12345\"").is_err());
+        assert!(serde_json::from_str::<super::Grid>("[1, 2, 3]").is_err());
+    }
+
+    #[test]
+    fn test_bytes() {
+        let grid = super::Grid::from_str("600008940900006100070040000200610000000000200089002000000060005000000030800001600".to_string());
+        let bytes = grid.to_bytes();
+        assert_eq!(bytes[..3], [0x60, 0x00, 0x08]);
+        assert_eq!(bytes[40], 0x00);
+        assert_eq!(super::Grid::from_bytes(&bytes).unwrap().to_str(), grid.to_str());
+
+        assert_eq!(super::Grid::from_bytes(&bytes[..40]).unwrap_err(), super::ParseError::InvalidLength(40));
+        let mut bytes = bytes;
+        bytes[1] = 0x0a;
+        assert_eq!(super::Grid::from_bytes(&bytes).unwrap_err(), super::ParseError::InvalidDigit { x: 3, y: 0, value: 10 });
+    }
+
+    #[test]
+    fn test_blanks() {
+        use super::{Grid, ParseError};
+
+        let puzzle = "600008940900006100070040000200610000000000200089002000000060005000000030800001600";
+        for blank in ['.', '_', ' '] {
+            let str = puzzle.replace('0', &blank.to_string());
+            assert_eq!(Grid::try_from_str(&str).unwrap().to_str(), puzzle);
+            assert_eq!(Grid::from_str_with_blank(&str, blank).unwrap().to_str(), puzzle);
+        }
+
+        let str = puzzle.replace('0', "*");
+        assert_eq!(Grid::try_from_str(&str).unwrap_err(), ParseError::InvalidCharacter('*'));
+        assert_eq!(Grid::from_str_with_blank(&str, '*').unwrap().to_str(), puzzle);
+        assert_eq!(Grid::from_str_with_blank(&puzzle.replace('0', "."), '*').unwrap_err(), ParseError::InvalidCharacter('.'));
+    }
+
+    #[test]
+    fn test_from_grid_str() {
+        let puzzle = "600008940900006100070040000200610000000000200089002000000060005000000030800001600";
+        let grid = super::Grid::from_str(puzzle.to_string());
+        assert_eq!(super::Grid::from_grid_str(&format!("{}", grid)).unwrap().to_str(), puzzle);
+
+        let str = "
+            6 . . | . . 8 | 9 4 .
+            9 . . | . . 6 | 1 . .
+            . 7 . | . 4 . | . . .
+            ------+-------+------
+            2 . . | 6 1 . | . . .
+            . . . | . . . | 2 . .
+            . 8 9 | . . 2 | . . .
+            ------+-------+------
+            . . . | . 6 . | . . 5
+            . . . | . . . | . 3 .
+            8 . . | . . 1 | 6 . .
+        ";
+        assert_eq!(super::Grid::from_grid_str(str).unwrap().to_str(), puzzle);
+        assert_eq!(super::Grid::from_grid_str(&str.replacen("8 . .", "", 1)).unwrap_err(), super::ParseError::InvalidLength(78));
+    }
+
+    #[test]
+    fn test_reset() {
+        let mut grid = super::Grid::from_str("600008940900006100070040000200610000000000200089002000000060005000000030800001600".to_string());
+        assert!(grid.solve().is_ok());
+
+        grid.reset();
+        assert_eq!(format!("{:?}", grid), format!("{:?}", super::Grid::default()));
+        assert!(grid.del_maybes.capacity() >= 81 && grid.set_cells.capacity() >= 81);
+
+        assert!(grid.load_str("100007090030020008009600500005300900010080002600004000300000010040000007007000300").is_ok());
+        assert!(grid.solve().is_ok());
+        assert_eq!(grid.to_str(), "162857493534129678789643521475312986913586742628794135356478219241935867897261354");
+        assert!(grid.load_str("1000").is_err());
+    }
+
+    #[test]
+    fn test_is_valid_solution() {
+        let solution = "625178943948326157371945862257619384463587291189432576792863415516294738834751629";
+        let grid = super::Grid::from_str(solution.to_string());
+        assert!(grid.is_valid_solution());
+        assert!(!grid.verify_solution());
+
+        let grid = super::Grid::from_str(solution.replacen('6', "0", 1));
+        assert!(!grid.is_valid_solution());
+        let grid = super::Grid::from_str(solution.replacen("62", "26", 1));
+        assert!(!grid.is_valid_solution());
+    }
+
+    #[test]
+    fn test_is_consistent() {
+        assert!(super::Grid::default().is_consistent());
+        assert!(super::Grid::from_str("600008940900006100070040000200610000000000200089002000000060005000000030800001600".to_string()).is_consistent());
+        // Duplicate 6 in the first column
+        assert!(!super::Grid::from_str("600008940900006100070040000200610000000000200089002000600060005000000030800001600".to_string()).is_consistent());
+        // Duplicate 2 in the first row
+        assert!(!super::Grid::from_str("234500200000023040000030400000600000300000000000230040040000654300000010203000004".to_string()).is_consistent());
+    }
+
+    #[test]
+    fn test_solve_checked() {
+        use super::SolveError;
+
+        let mut grid = super::Grid::from_str("600008940900006100070040000200610000000000200089002000000060005000000030800001600".to_string());
+        assert_eq!(grid.solve_checked(), Ok(()));
+        assert!(grid.verify_solution());
+
+        let mut grid = super::Grid::from_str("625178943048026157071045862257619384463587291189432576792863415516294738834751629".to_string());
+        let mut solution = grid.clone();
+        assert!(solution.solve().is_ok());
+        assert_eq!(grid.solve_checked(), Err(SolveError::NotUnique));
+        assert!(grid.verify_solution());
+        assert_eq!(grid.to_str(), solution.to_str());
+
+        let mut grid = super::Grid::from_str("234500200000023040000030400000600000300000000000230040040000654300000010203000004".to_string());
+        assert!(matches!(grid.solve_checked(), Err(SolveError::Contradiction { .. })));
+    }
+
+    #[test]
+    fn test_contradiction_location() {
+        use super::SolveError;
+
+        // The 5 given at (0, 0) clashes with that at (1, 0), and is the last to be placed.
+        let mut grid = super::Grid::from_str(format!("55{}", "0".repeat(79)));
+        let error = SolveError::Contradiction { x: 0, y: 0, digit: 5 };
+        assert_eq!(grid.clone().solve_checked(), Err(error));
+        assert_eq!(grid.solve(), Err(error));
+
+        // Contradictions reached while guessing are still reported.
+        let mut grid = super::Grid::from_str("234500200000023040000030400000600000300000000000230040040000654300000010203000004".to_string());
+        let error = grid.clone().solve_checked().unwrap_err();
+        assert!(matches!(error, SolveError::Contradiction { .. }));
+        assert_eq!(grid.solve(), Err(error));
+    }
+
+    #[test]
+    fn test_parse() {
+        use super::ParseError;
+
+        let grid = "600008940900006100070040000200610000000000200089002000000060005000000030800001600".parse::<super::Grid>().unwrap();
+        assert_eq!(grid.to_str(), "600008940900006100070040000200610000000000200089002000000060005000000030800001600");
+        assert_eq!(grid.to_string().parse::<super::Grid>().unwrap().to_str(), grid.to_str());
+
+        assert_eq!("1234".parse::<super::Grid>().unwrap_err(), ParseError::InvalidLength(4));
+    }
+
+    #[test]
+    fn test_solve_logical() {
+        use super::SolveOutcome;
+
+        let mut grid = super::Grid::from_str("400001309000000006010800000000040000005000070000209004068900500200007080500000001".to_string());
+        assert_eq!(grid.solve_logical(), SolveOutcome::Solved);
+        assert!(grid.verify_solution());
+
+        let mut grid = super::Grid::from_str("800000320007000600009274800000705006050040008600000000000916000000500000100000035".to_string());
+        assert_eq!(grid.solve_logical(), SolveOutcome::Stuck);
+        assert!(grid.is_consistent());
+        assert!(grid.to_str().contains('0'));
+
+        let mut grid = super::Grid::from_str(format!("55{}", "0".repeat(79)));
+        assert_eq!(grid.solve_logical(), SolveOutcome::Contradiction { x: 0, y: 0, digit: 5 });
+    }
+
+    #[test]
+    fn test_solve_with_stats() {
+        // Solved by eliminations and hidden singles alone.
+        let mut grid = super::Grid::from_str("000500000630000001000263749060900530000075000003008900040030000002050060500000010".to_string());
+        let stats = grid.solve_with_stats().unwrap();
+        assert!(grid.verify_solution());
+        assert_eq!((stats.guesses, stats.backtracks), (0, 0));
+        assert!(stats.hidden_singles > 0);
+        assert!(stats.eliminations > 0);
+
+        let mut grid = super::Grid::from_str("000000012000000003002300400001800005060070800000009000008500000900040500470006000".to_string());
+        let stats = grid.solve_with_stats().unwrap();
+        assert!(grid.verify_solution());
+        assert!(stats.guesses > stats.backtracks);
+        assert!(stats.backtracks > 0);
+    }
+
+    #[test]
+    fn test_solve_with_observer() {
+        use super::StepEvent;
+
+        // Replaying the observed cell sets reproduces the solution, as the final guesses are observed last.
+        let puzzle = super::Grid::from_str("000000012000000003002300400001800005060070800000009000008500000900040500470006000".to_string());
+        let mut grid = puzzle.clone();
+        let mut events = Vec::new();
+        assert!(grid.solve_with_observer(&mut |&event| events.push(event)).is_ok());
+        assert!(grid.verify_solution());
+
+        let mut cells = [[0; 9]; 9];
+        let (mut guesses, mut backtracks) = (0, 0);
+        for event in events {
+            match event {
+                StepEvent::Set { digit, x, y } => cells[x][y] = digit,
+                StepEvent::Eliminate { digit, .. } => assert!((1..=9).contains(&digit)),
+                StepEvent::Guess { .. } => guesses += 1,
+                StepEvent::Backtrack { .. } => backtracks += 1,
+            }
+        }
+        assert_eq!(super::Grid::from_cells(cells).unwrap().to_str(), grid.to_str());
+
+        let stats = puzzle.clone().solve_with_stats().unwrap();
+        assert_eq!((guesses, backtracks), (stats.guesses, stats.backtracks));
+    }
+
+    #[test]
+    fn test_grid_sizes() {
+        use super::{Grid4, Grid16};
+
+        let mut grid = Grid4::try_from_str("1.3.3..2.1..4..1").unwrap();
+        assert!(grid.has_unique_solution());
+        assert!(grid.solve().is_ok());
+        assert!(grid.verify_solution());
+        assert_eq!(grid.to_str(), "1234341221434321");
+        assert_eq!(grid.to_string(), "+--+--+\n|12|34|\n|34|12|\n+--+--+\n|21|43|\n|43|21|\n+--+--+");
+        assert!(Grid4::try_from_str("5000000000000000").is_err());
+
+        // Solve an empty grid, then clear cells from the solution and solve it again.
+        let mut grid = Grid16::default();
+        assert!(grid.solve().is_ok());
+        assert!(grid.verify_solution());
+        let solution = grid.to_str();
+        assert!(solution.contains('G') && !solution.contains('0'));
+
+        let puzzle = solution.chars().enumerate()
+            .map(|(i, char)| if i % 3 == 0 { '.' } else { char })
+            .collect::<alloc::string::String>();
+        let mut grid = Grid16::try_from_str(&puzzle).unwrap();
+        assert!(grid.solve().is_ok());
+        assert!(grid.verify_solution());
+        assert!(grid.to_str().chars().zip(puzzle.chars()).all(|(char, given)| given == '.' || char == given));
+        assert_eq!(grid.to_string().parse::<Grid16>().unwrap().to_str(), grid.to_str());
+    }
+
+    #[test]
+    fn test_diagonals() {
+        use super::Constraint;
+
+        let puzzle = super::Grid::from_str("000000006000000000000300100060700003002004008090060204040080000001050900000000000".to_string());
+        assert_eq!(puzzle.count_solutions(2), 2);
+
+        let mut grid = puzzle.clone().with_constraint(Constraint::Diagonals);
+        assert!(grid.has_unique_solution());
+        assert!(grid.solve().is_ok());
+        assert!(grid.verify_solution());
+        assert_eq!(grid.to_str(), "453291876126578349789346125864712593512934768397865214245189637671453982938627451");
+
+        // A solution to the standard rules need not satisfy the diagonals.
+        let mut grid = super::Grid::from_str("600008940900006100070040000200610000000000200089002000000060005000000030800001600".to_string());
+        assert!(grid.solve().is_ok());
+        let solved = grid.clone().with_constraint(Constraint::Diagonals);
+        assert!(!solved.is_valid_solution());
+        assert!(!solved.is_consistent());
+        assert!(solved.clone().solve().is_err());
+
+        // Constraints are kept when loading another grid.
+        let mut grid = super::Grid::default().with_constraint(Constraint::Diagonals);
+        grid.load_str("000000006000000000000300100060700003002004008090060204040080000001050900000000000").unwrap();
+        assert!(grid.solve().is_ok());
+        assert!(grid.verify_solution());
+        assert_eq!(grid.to_str(), "453291876126578349789346125864712593512934768397865214245189637671453982938627451");
+    }
+
+    #[test]
+    fn test_canonical() {
+        let puzzle = "000000010400000000020000000000050407008000300001090000300400200050100000000806000";
+        let grid = super::Grid::from_str(puzzle.to_string());
+        let canonical = grid.canonical();
+        assert!(canonical.to_str().as_str() <= puzzle);
+        assert_eq!(canonical.canonical().to_str(), canonical.to_str());
+        assert_eq!(canonical.count_solutions(2), 1);
+
+        // Transpose, swap the first two rows and the last two stacks, and relabel each digit as its complement.
+        let board = grid.board();
+        let mut cells = [[0u8; 9]; 9];
+        for y in 0..9 {
+            for x in 0..9 {
+                let (x2, y2) = (y, x);
+                let y2 = match y2 { 0 => 1, 1 => 0, y2 => y2 };
+                let x2 = match x2 / 3 { 1 => x2 + 3, 2 => x2 - 3, _ => x2 };
+                cells[x2][y2] = if board[x][y] == 0 { 0 } else { 10 - board[x][y] };
+            }
+        }
+        let equivalent = super::Grid::from_cells(cells).unwrap();
+        assert_ne!(equivalent.to_str(), grid.to_str());
+        assert_eq!(equivalent.canonical().to_str(), canonical.to_str());
+        assert!(grid.is_equivalent(&equivalent) && equivalent.is_equivalent(&grid));
+
+        let other = super::Grid::from_str("000000012000000003002300400001800005060070800000009000008500000900040500470006000".to_string());
+        assert_ne!(other.canonical().to_str(), canonical.to_str());
+        assert!(!grid.is_equivalent(&other));
+        assert!(!grid.is_equivalent(&super::Grid::default()));
+    }
+
+    #[test]
+    fn test_transforms() {
+        use super::Constraint;
+
+        let grid = super::Grid::from_str("400001309000000006010800000000040000005000070000209004068900500200007080500000001".to_string());
+        let mut solution = grid.clone();
+        assert!(solution.solve().is_ok());
+
+        assert_eq!(grid.rotate90().to_str(), grid.transpose().reflect_horizontal().to_str());
+        assert_eq!(grid.rotate90().rotate90().rotate90().rotate90().to_str(), grid.to_str());
+        assert_eq!(grid.reflect_horizontal().reflect_horizontal().to_str(), grid.to_str());
+        assert_eq!(grid.transpose().get(1, 0), grid.get(0, 1));
+        assert_eq!(&grid.reflect_horizontal().to_str()[..9], "903100004");
+        assert_eq!(&grid.relabel([9, 8, 7, 6, 5, 4, 3, 2, 1]).to_str()[..9], "600009701");
+
+        // Solutions are transformed alike, and remain valid.
+        for transformed in [grid.rotate90(), grid.transpose(), grid.relabel([2, 3, 4, 5, 6, 7, 8, 9, 1])] {
+            let mut transformed = transformed;
+            assert!(transformed.solve().is_ok());
+            assert!(transformed.verify_solution());
+        }
+        let mut rotated = grid.rotate90();
+        assert!(rotated.solve().is_ok());
+        assert_eq!(rotated.to_str(), solution.rotate90().to_str());
+
+        // Constraints are carried over, as the diagonals map onto each other.
+        let grid = super::Grid::from_str("000000006000000000000300100060700003002004008090060204040080000001050900000000000".to_string())
+            .with_constraint(Constraint::Diagonals);
+        assert!(grid.rotate90().has_unique_solution());
+        assert!(grid.reflect_horizontal().has_unique_solution());
+    }
+
+    #[test]
+    fn test_eq() {
+        extern crate std;
+        use std::collections::HashSet;
+
+        let puzzle = "400001309000000006010800000000040000005000070000209004068900500200007080500000001";
+        let grid = super::Grid::from_str(puzzle.to_string());
+        let mut stepped = grid.clone();
+        stepped.apply_step(stepped.next_step().unwrap()).unwrap();
+
+        // Grids with the same digits are equal, regardless of the progress of the solver.
+        assert_eq!(grid, stepped);
+        assert_eq!(grid, super::Grid::from_cells(grid.board()).unwrap());
+        let mut solution = grid.clone();
+        assert!(solution.solve().is_ok());
+        assert_ne!(grid, solution);
+
+        let set = [grid.clone(), stepped, solution.clone(), grid.transpose().transpose()].into_iter().collect::<HashSet<_>>();
+        assert_eq!(set.len(), 2);
+        assert!(set.contains(&grid) && set.contains(&solution));
+    }
+
+    #[test]
+    fn test_givens() {
+        let puzzle = "600008940900006100070040000200610000000000200089002000000060005000000030800001600";
+        let mut grid = super::Grid::from_str(puzzle.to_string());
+        let givens = grid.givens().collect::<Vec<_>>();
+        assert_eq!(givens.len(), puzzle.chars().filter(|&c| c != '0').count());
+        assert_eq!(grid.clue_count(), givens.len());
+        assert_eq!(givens[..3], [(0, 0, 6), (5, 0, 8), (6, 0, 9)]);
+
+        // Cells placed by the solver are not givens.
+        assert!(grid.solve().is_ok());
+        assert_eq!(grid.givens().collect::<Vec<_>>(), givens);
+        assert_eq!(grid.clue_count(), givens.len());
+
+        let transposed = grid.transpose();
+        assert!(transposed.givens().all(|(x, y, digit)| givens.contains(&(y, x, digit))));
+        assert_eq!(transposed.givens().count(), givens.len());
+        grid.reset();
+        assert_eq!(grid.givens().count(), 0);
+        assert_eq!(grid.clue_count(), 0);
+    }
+
+    #[test]
+    fn test_place() {
+        use super::PlaceError;
+
+        let puzzle = "600008940900006100070040000200610000000000200089002000000060005000000030800001600";
+        let mut grid = super::Grid::from_str(puzzle.to_string());
+        assert_eq!(grid.place(9, 0, 1), Err(PlaceError::OutOfBounds { x: 9, y: 0 }));
+        assert_eq!(grid.place(1, 0, 0), Err(PlaceError::InvalidDigit(0)));
+        assert_eq!(grid.place(1, 0, 10), Err(PlaceError::InvalidDigit(10)));
+        assert_eq!(grid.place(0, 0, 1), Err(PlaceError::Occupied { x: 0, y: 0 }));
+        assert_eq!(grid.erase(0, 0), Err(PlaceError::Given { x: 0, y: 0 }));
+
+        // The givens are placed, but no further digits are deduced.
+        assert!(grid.place(1, 0, 6).is_err());
+        assert_eq!(grid.candidate_digits(1, 0).collect::<Vec<_>>(), [1, 2, 3, 5]);
+        assert_eq!(grid.to_str(), puzzle);
+
+        assert_eq!(grid.place(1, 0, 1), Ok(()));
+        assert_eq!(grid.get(1, 0), 1);
+        assert_eq!(grid.candidates(1, 0), 0);
+        assert_eq!(grid.candidate_digits(2, 0).collect::<Vec<_>>(), [2, 3, 5]);
+        assert_eq!(grid.clue_count(), puzzle.chars().filter(|&c| c != '0').count());
+
+        assert_eq!(grid.erase(1, 0), Ok(()));
+        assert_eq!(grid.erase(1, 0), Ok(()));
+        assert_eq!(grid.to_str(), puzzle);
+        assert_eq!(grid.candidate_digits(1, 0).collect::<Vec<_>>(), [1, 2, 3, 5]);
+        assert_eq!(grid.candidate_digits(2, 0).collect::<Vec<_>>(), [1, 2, 3, 5]);
+
+        // The grid can still be solved after being edited.
+        assert_eq!(grid.place(1, 0, 2), Ok(()));
+        assert!(grid.solve().is_ok());
+        assert!(grid.verify_solution());
+    }
+
+    #[test]
+    fn test_recompute_candidates() {
+        let puzzle = "600008940900006100070040000200610000000000200089002000000060005000000030800001600";
+        let mut grid = super::Grid::from_str(puzzle.to_string());
+        grid.recompute_candidates();
+        assert_eq!(grid.to_str(), puzzle);
+        assert_eq!(grid.candidate_digits(1, 0).collect::<Vec<_>>(), [1, 2, 3, 5]);
+
+        // Modify the cells directly, as though the state of the grid became stale.
+        grid.cells[1][0] = 2;
+        grid.recompute_candidates();
+        assert_eq!(grid.candidates(1, 0), 0);
+        assert_eq!(grid.candidate_digits(2, 0).collect::<Vec<_>>(), [1, 3, 5]);
+        assert_eq!(grid.row_digit_counters[0][1], 0);
+        assert!(grid.set_cells.is_empty() && grid.del_maybes.is_empty());
+        assert!(grid.solve().is_ok());
+        assert!(grid.verify_solution());
+
+        // Contradicting digits are left for the solver to report.
+        let mut grid = super::Grid::from_str(puzzle.to_string());
+        grid.solve().unwrap();
+        grid.cells[1][0] = 6;
+        grid.recompute_candidates();
+        assert_eq!(grid.get(1, 0), 6);
+        assert!(grid.set_cells.iter().any(|op| op == (6, (1, 0))));
+        assert!(grid.solve().is_err());
+    }
+
+    #[test]
+    fn test_candidates_str() {
+        let mut grid = super::Grid::from_str("625178943048026157071045862257619384463587291189432576792863415516294738834751629".to_string());
+        assert_eq!(grid.solve_logical(), super::SolveOutcome::Stuck);
+        let str = grid.candidates_str();
+        let lines = str.lines().collect::<Vec<_>>();
+        assert_eq!(lines.len(), 9);
+        assert_eq!(lines[0], "6  2  5  1  7  8  9  4  3");
+        assert_eq!(lines[1], "39 4  8  39 2  6  1  5  7");
+
+        grid.solve().unwrap();
+        assert_eq!(grid.candidates_str().lines().next(), Some("6 2 5 1 7 8 9 4 3"));
+        assert_eq!(grid.candidates_str().replace([' ', '\n'], ""), grid.to_str());
+    }
+
+    #[test]
+    fn test_solve_str() {
+        let solution = "625178943948326157371945862257619384463587291189432576792863415516294738834751629";
+        let puzzle = "600008940900006100070040000200610000000000200089002000000060005000000030800001600";
+        assert_eq!(super::solve_str(puzzle).as_deref(), Ok(solution));
+        assert_eq!(super::solve_str(&super::Grid::from_str(puzzle.to_string()).to_string()).as_deref(), Ok(solution));
+
+        assert_eq!(super::solve_str("123").unwrap_err(), "grid input of unexpected length 3");
+        assert_eq!(super::solve_str(&puzzle.replace('9', "x")).unwrap_err(), "unexpected character 'x' in grid string");
+        assert!(super::solve_str(&puzzle.replacen('0', "6", 1)).unwrap_err().starts_with("grid has no solution"));
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn test_solve_reader() {
+        let input = "600008940900006100070040000200610000000000200089002000000060005000000030800001600\n\n\
+            123\n\
+            660008940900006100070040000200610000000000200089002000000060005000000030800001600\r\n\
+            000000010400000000020000000000050407008000300001090000300400200050100000000806000";
+        let mut output = Vec::new();
+        assert_eq!(super::solve_reader(input.as_bytes(), &mut output).unwrap(), (2, 2));
+
+        let output = alloc::string::String::from_utf8(output).unwrap();
+        let lines: Vec<_> = output.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[0], "625178943948326157371945862257619384463587291189432576792863415516294738834751629");
+        assert_eq!(lines[1], "error: grid input of unexpected length 3");
+        assert!(lines[2].starts_with("error: grid has no solution"));
+        assert_eq!(lines[3], "693784512487512936125963874932651487568247391741398625319475268856129743274836159");
+    }
+
+    #[test]
+    #[cfg(feature = "ffi")]
+    fn test_ffi() {
+        use super::ffi::{sudoku_solve, SUDOKU_INVALID_INPUT, SUDOKU_NO_SOLUTION, SUDOKU_OK};
+
+        let puzzle = c"600008940900006100070040000200610000000000200089002000000060005000000030800001600";
+        let mut out = [0u8; 81];
+        assert_eq!(unsafe { sudoku_solve(puzzle.as_ptr(), out.as_mut_ptr().cast()) }, SUDOKU_OK);
+        assert_eq!(&out[..], b"625178943948326157371945862257619384463587291189432576792863415516294738834751629");
+
+        let mut out = [0u8; 81];
+        let unsolvable = c"660008940900006100070040000200610000000000200089002000000060005000000030800001600";
+        assert_eq!(unsafe { sudoku_solve(unsolvable.as_ptr(), out.as_mut_ptr().cast()) }, SUDOKU_NO_SOLUTION);
+        assert_eq!(unsafe { sudoku_solve(c"123".as_ptr(), out.as_mut_ptr().cast()) }, SUDOKU_INVALID_INPUT);
+        assert_eq!(unsafe { sudoku_solve(puzzle.as_ptr(), core::ptr::null_mut()) }, SUDOKU_INVALID_INPUT);
+        assert_eq!(unsafe { sudoku_solve(core::ptr::null(), out.as_mut_ptr().cast()) }, SUDOKU_INVALID_INPUT);
+        assert_eq!(out, [0; 81]);
+    }
+
+    #[test]
+    #[cfg(feature = "rayon")]
+    fn test_solve_parallel() {
+        for puzzle in [
+            "000000012000000003002300400001800005060070800000009000008500000900040500470006000",
+            "000000010400000000020000000000050407008000300001090000300400200050100000000806000",
+        ] {
+            let mut grid = super::Grid::from_str(puzzle.to_string());
+            let mut expected = grid.clone();
+            assert!(grid.solve_parallel().is_ok());
+            assert!(expected.solve().is_ok());
+            assert_eq!(grid, expected);
+            assert!(grid.verify_solution());
+        }
+
+        let mut grid = super::Grid::default();
+        assert!(grid.solve_parallel().is_ok());
+        assert!(grid.verify_solution());
+
+        let mut grid = super::Grid::from_str("234500200000023040000030400000600000300000000000230040040000654300000010203000004".to_string());
+        assert!(matches!(grid.solve_parallel(), Err(super::SolveError::Contradiction { .. })));
+    }
+
+    #[test]
+    fn test_unsolvable() {
+        use super::{SolveError, SolveOutcome};
+
+        // Logic alone finds no contradiction in this grid, only searching every possibility shows it has no solution.
+        let puzzle = "840600000000040800000000609000000043090000000070030000000203000000100000200000400";
+        let grid = super::Grid::from_str(puzzle.to_string());
+        assert!(grid.is_consistent());
+        assert_eq!(grid.clone().solve_logical(), SolveOutcome::Stuck);
+        assert!(matches!(grid.clone().solve(), Err(SolveError::Contradiction { .. })));
+        assert!(matches!(grid.clone().solve_checked(), Err(SolveError::Contradiction { .. })));
+        assert_eq!(grid.count_solutions(1), 0);
+
+        // Merely hard grids that require a lot of guessing are still solved.
+        let mut grid = super::Grid::from_str("000000012000000003002300400001800005060070800000009000008500000900040500470006000".to_string());
+        assert_eq!(grid.clone().solve_logical(), SolveOutcome::Stuck);
+        assert!(grid.solve().is_ok());
+        assert!(grid.verify_solution());
+    }
 
     #[test]
-    fn test_solver() {
-        let mut grid = super::Grid::from_str("600008940900006100070040000200610000000000200089002000000060005000000030800001600".to_string());
+    fn test_generate_symmetric() {
+        use super::Symmetry;
+
+        let mut rng = rand_pcg::Pcg32::seed_from_u64(0);
+        for symmetry in [Symmetry::None, Symmetry::Rotational180, Symmetry::Diagonal] {
+            let grid = super::Grid::generate_symmetric(symmetry, &mut rng);
+            assert!(grid.has_unique_solution());
+
+            let board = grid.board();
+            for (x, y) in (0..81).map(|i| (i % 9, i / 9)) {
+                match symmetry {
+                    Symmetry::None => (),
+                    Symmetry::Rotational180 => assert_eq!(board[x][y] == 0, board[8 - x][8 - y] == 0),
+                    Symmetry::Diagonal => assert_eq!(board[x][y] == 0, board[y][x] == 0),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_minimize() {
+        let puzzle = "600008940900006100070040000200610000000000200089002000000060005000000030800001600";
+        let solution = "625178943948326157371945862257619384463587291189432576792863415516294738834751629";
+
+        // Add redundant clues from the solution to the puzzle.
+        let mut cells = super::Grid::from_str(puzzle.to_string()).board();
+        let full = super::Grid::from_str(solution.to_string()).board();
+        for x in 0..9 {
+            cells[x][4] = full[x][4];
+        }
+        let mut grid = super::Grid::from_cells(cells).unwrap();
+        let clues = grid.clue_count();
+        grid.minimize();
+        assert!(grid.clue_count() < clues);
+        assert!(grid.has_unique_solution());
+        assert!(grid.givens().all(|(x, y, digit)| full[x][y] == digit));
+
+        // Each remaining given is required for the solution to be unique.
+        for (x, y, _) in grid.givens() {
+            let mut cells = grid.board();
+            cells[x][y] = 0;
+            assert!(!super::Grid::from_cells(cells).unwrap().has_unique_solution());
+        }
+
+        let mut grid = super::Grid::from_str("000000000000000000000000000000000000000000000000000000000000000000000000123456780".to_string());
+        grid.minimize();
+        assert_eq!(grid.clue_count(), 8);
+    }
+
+    #[test]
+    fn test_estimate_branch_factor() {
+        let easy = super::Grid::from_str("000500000630000001000263749060900530000075000003008900040030000002050060500000010".to_string());
+        assert_eq!(easy.estimate_branch_factor(), 0.0);
+
+        let hard = super::Grid::from_str("000000012000000003002300400001800005060070800000009000008500000900040500470006000".to_string());
+        let estimate = hard.estimate_branch_factor();
+        assert!(estimate >= 1.0);
+        assert_eq!(estimate, hard.estimate_branch_factor());
+
+        let unsolvable = super::Grid::from_str("840600000000040800000000609000000043090000000070030000000203000000100000200000400".to_string());
+        assert_eq!(unsolvable.estimate_branch_factor(), f64::INFINITY);
+    }
+
+    #[test]
+    fn test_solve_seeded() {
+        let mut rng = rand_pcg::Pcg32::seed_from_u64(0);
+
+        let puzzle = "000000012000000003002300400001800005060070800000009000008500000900040500470006000";
+        let mut grid = super::Grid::from_str(puzzle.to_string());
+        let mut expected = grid.clone();
+        assert!(grid.solve_seeded(&mut rng).is_ok());
+        assert!(expected.solve().is_ok());
+        assert_eq!(grid, expected);
+
+        let mut first = super::Grid::default();
+        let mut second = super::Grid::default();
+        assert!(first.solve_seeded(&mut rng).is_ok());
+        assert!(second.solve_seeded(&mut rng).is_ok());
+        assert!(first.verify_solution() && second.verify_solution());
+        assert_ne!(first, second);
+
+        let mut grid = super::Grid::from_str("840600000000040800000000609000000043090000000070030000000203000000100000200000400".to_string());
+        assert!(grid.solve_seeded(&mut rng).is_err());
+    }
+
+    #[test]
+    fn test_add_cage() {
+        let solution = "625178943948326157371945862257619384463587291189432576792863415516294738834751629";
+        let digits: Vec<u8> = solution.bytes().map(|b| b - b'0').collect();
+        let add_cages = |grid: &mut super::Grid| {
+            for y in 0..9 {
+                for xs in [&[0, 1][..], &[2, 3], &[4, 5], &[6, 7, 8]] {
+                    let cells: Vec<_> = xs.iter().map(|&x| (x, y)).collect();
+                    grid.add_cage(&cells, cells.iter().map(|&(x, y)| digits[y * 9 + x]).sum());
+                }
+            }
+        };
+
+        let puzzle = super::Grid::from_str("600070940000020150000040060000000000000000000000000000000000000000000000000000000".to_string());
+        assert_eq!(puzzle.count_solutions(2), 2);
+
+        let mut grid = puzzle.clone();
+        add_cages(&mut grid);
+        assert!(grid.has_unique_solution());
         assert!(grid.solve().is_ok());
-        assert_eq!(grid.to_str(), "625178943948326157371945862257619384463587291189432576792863415516294738834751629");
+        assert!(grid.verify_solution());
+        assert_eq!(grid.to_str(), solution);
 
-        let mut grid2 = super::Grid::from_str("100007090030020008009600500005300900010080002600004000300000010040000007007000300".to_string());
-        assert!(grid2.solve().is_ok());
-        assert_eq!(grid2.to_str(), "162857493534129678789643521475312986913586742628794135356478219241935867897261354");
+        // A solution to the standard rules need not add up to the sums of the cages.
+        let mut grid = super::Grid::from_str(solution.to_string());
+        grid.add_cage(&[(0, 0), (1, 0)], 9);
+        assert!(!grid.is_valid_solution());
+        assert!(!grid.is_consistent());
+        assert!(grid.solve().is_err());
 
-        
-        let mut grid3 = super::Grid::from_str("234500200000023040000030400000600000300000000000230040040000654300000010203000004".to_string());
-        assert!(grid3.solve().is_err());
+        // Blank cells of a cage need digits left to reach its sum, not only digits below it.
+        for (sum, consistent) in [(9, false), (10, true), (11, true)] {
+            let mut grid = super::Grid::from_str(format!("45{}", "0".repeat(79)));
+            grid.add_cage(&[(0, 0), (1, 0), (2, 0)], sum);
+            assert_eq!(grid.is_consistent(), consistent, "sum {}", sum);
+        }
+        for (sum, consistent) in [(11, false), (12, true)] {
+            let mut grid = super::Grid::from_str(format!("9{}", "0".repeat(80)));
+            grid.add_cage(&[(0, 0), (1, 0), (2, 0)], sum);
+            assert_eq!(grid.is_consistent(), consistent, "sum {}", sum);
+        }
+
+        // Cages are kept when loading another grid, and moved along with the cells when transformed.
+        let mut grid = super::Grid::default();
+        add_cages(&mut grid);
+        grid.load_str("600070940000020150000040060000000000000000000000000000000000000000000000000000000").unwrap();
+        let mut rotated = grid.rotate90();
+        assert!(grid.solve().is_ok());
+        assert_eq!(grid.to_str(), solution);
+        assert!(rotated.solve().is_ok());
+        assert!(rotated.verify_solution());
+        assert_eq!(rotated, grid.rotate90());
+    }
+
+    #[test]
+    fn test_solved() {
+        let puzzle = super::Grid::from_str("600008940900006100070040000200610000000000200089002000000060005000000030800001600".to_string());
+        let original = puzzle.clone();
+        let solution = puzzle.solved().unwrap();
+        assert_eq!(puzzle.to_str(), original.to_str());
+        assert!(solution.verify_solution());
+        assert_eq!(solution.to_str(), "625178943948326157371945862257619384463587291189432576792863415516294738834751629");
+
+        let puzzle = super::Grid::from_str("840600000000040800000000609000000043090000000070030000000203000000100000200000400".to_string());
+        assert!(matches!(puzzle.solved(), Err(super::SolveError::Contradiction { .. })));
+    }
+
+    #[test]
+    fn test_to_str_partial() {
+        let solution = "625178943948326157371945862257619384463587291189432576792863415516294738834751629";
+        let half = format!("{}{}", &solution[..41], "0".repeat(40));
+        let mut grid = super::Grid::from_str(half.clone());
+        assert_eq!(grid.to_str(), half);
+
+        // Digits placed so far are included alongside the blank cells.
+        assert!(grid.place(5, 4, 7).is_ok());
+        assert!(grid.place(8, 8, 9).is_ok());
+        let mut expected = half.into_bytes();
+        expected[4 * 9 + 5] = b'7';
+        expected[8 * 9 + 8] = b'9';
+        assert_eq!(grid.to_str().into_bytes(), expected);
+    }
+
+    #[test]
+    fn test_with_regions() {
+        let rows = [
+            "000112122",
+            "000111222",
+            "001041225",
+            "333414552",
+            "333444555",
+            "336445855",
+            "663777488",
+            "666777888",
+            "667677888",
+        ];
+        let mut regions = [[0u8; 9]; 9];
+        for (y, row) in rows.iter().enumerate() {
+            for (x, region) in row.bytes().enumerate() {
+                regions[x][y] = region - b'0';
+            }
+        }
+
+        let puzzle = super::Grid::from_str("000000000000070001000000000000000000000001602000000010000002090090000075040089006".to_string());
+        assert_eq!(puzzle.count_solutions(2), 2);
+
+        let mut grid = puzzle.clone().with_regions(regions);
+        assert!(grid.has_unique_solution());
+        assert!(grid.solve().is_ok());
+        assert!(grid.verify_solution());
+        assert_eq!(grid.to_str(), "187423569432976851961548723516237984379851642823695417654712398298364175745189236");
+
+        // The solution need not satisfy the standard blocks, nor a solution of the standard blocks the regions.
+        assert!(!super::Grid::from_str(grid.to_str()).is_valid_solution());
+        let solved = super::Grid::from_str("625178943948326157371945862257619384463587291189432576792863415516294738834751629".to_string());
+        assert!(!solved.with_regions(regions).is_valid_solution());
+
+        // Regions are moved along with the cells when transformed.
+        let mut rotated = puzzle.with_regions(regions).rotate90();
+        assert!(rotated.solve().is_ok());
+        assert!(rotated.verify_solution());
+        assert_eq!(rotated, grid.rotate90());
+    }
+
+    #[test]
+    fn test_peers() {
+        let grid = super::Grid::default();
+        let peers: Vec<_> = grid.peers(4, 1).collect();
+        assert_eq!(peers.len(), 20);
+        assert_eq!(peers[..10], [(3, 0), (4, 0), (5, 0), (0, 1), (1, 1), (2, 1), (3, 1), (5, 1), (6, 1), (7, 1)]);
+        assert!(peers.contains(&(4, 8)) && peers.contains(&(5, 2)));
+        assert!(!peers.contains(&(4, 1)) && !peers.contains(&(6, 2)));
+
+        assert!((0..81).all(|i| grid.peers(i % 9, i / 9).count() == 20));
+
+        // The diagonal through a corner adds the six cells outside of its block.
+        let grid = super::Grid::default().with_constraint(super::Constraint::Diagonals);
+        assert_eq!(grid.peers(0, 0).count(), 26);
+        assert!(grid.peers(0, 0).any(|cell| cell == (8, 8)));
+        assert_eq!(grid.peers(1, 0).count(), 20);
+    }
+
+    #[test]
+    fn test_complete_input() {
+        let solution = "625178943948326157371945862257619384463587291189432576792863415516294738834751629";
+        let mut grid = super::Grid::from_str(solution.to_string());
+        let stats = grid.solve_with_stats().unwrap();
+        assert_eq!(stats.guesses, 0);
+        assert!(grid.verify_solution());
+        assert_eq!(grid.to_str(), solution);
+
+        // A duplicate digit is reported at the first cell involved, rather than being replaced.
+        let mut invalid = solution.to_string();
+        invalid.replace_range(1..2, "6");
+        let mut grid = super::Grid::from_str(invalid);
+        assert_eq!(grid.solve(), Err(super::SolveError::Contradiction { x: 0, y: 0, digit: 6 }));
+        assert!(!grid.verify_solution());
+
+        // So is a cage that does not add up to its sum, despite the digits satisfying the other rules.
+        let mut grid = super::Grid::from_str(solution.to_string());
+        grid.add_cage(&[(4, 4), (5, 4)], 14);
+        assert_eq!(grid.solve(), Err(super::SolveError::Contradiction { x: 4, y: 4, digit: 8 }));
+    }
+
+    #[test]
+    fn test_cells() {
+        let puzzle = "600008940900006100070040000200610000000000200089002000000060005000000030800001600";
+        let grid = super::Grid::from_str(puzzle.to_string());
+        assert_eq!(grid.to_cells(), grid.clone().into_cells());
+
+        let cells = grid.clone().into_cells();
+        assert_eq!(cells[0][0], 6);
+        assert_eq!(cells[7][0], 4);
+        assert_eq!(cells[0][1], 9);
+        assert_eq!(super::Grid::from_cells(cells).unwrap().to_str(), puzzle);
+
+        let mut solved = grid.clone();
+        assert!(solved.solve().is_ok());
+        assert_eq!(solved.to_cells()[1][0], 2);
+        assert_eq!(solved.to_cells(), solved.clone().into_cells());
+        assert!((0..81).all(|i| solved.to_cells()[i % 9][i / 9] == solved.get(i % 9, i / 9)));
+    }
+
+    #[test]
+    fn test_filled_count() {
+        let mut grid = super::Grid::from_str("000400002600000000003150800094000000000390050070006000400020001705000030102500400".to_string());
+        assert_eq!(grid.filled_count(), grid.clue_count());
+
+        // With only hidden singles, the solver is stuck part of the way through.
+        let config = super::SolverConfig::new().without_techniques().technique(super::Technique::HiddenSingles, true).guessing(false);
+        assert!(grid.solve_with(&config).is_err());
+        let partial = grid.filled_count();
+        assert!(partial > grid.clue_count() && partial < 81);
+
+        assert!(grid.solve().is_ok());
+        assert_eq!(grid.filled_count(), 81);
+    }
+
+    #[test]
+    fn test_empty_grid() {
+        let empty = super::Grid::from_str("0".repeat(81));
+        assert_eq!(empty, super::Grid::default());
+        assert!(!empty.has_unique_solution());
+        assert_eq!(empty.count_solutions(100), 100);
+
+        let mut grid = empty.clone();
+        let stats = grid.solve_with_stats().unwrap();
+        assert_eq!(stats.backtracks, 0);
+        assert!(grid.verify_solution());
+        assert!(grid.is_valid_solution());
+    }
+
+    #[test]
+    fn test_anti_knight() {
+        use super::Constraint;
+
+        let puzzle = super::Grid::from_str("000000000000000000000000000000000000000000000003000006000008001002009048085001639".to_string());
+        assert_eq!(puzzle.count_solutions(2), 2);
+
+        let mut grid = puzzle.clone().with_constraint(Constraint::AntiKnight);
+        assert!(grid.has_unique_solution());
+        assert!(grid.solve().is_ok());
+        assert!(grid.verify_solution());
+        assert_eq!(grid.to_str(), "397415862518627493246893157924186375671352984853974216439768521162539748785241639");
+
+        // A solution to the standard rules need not keep digits a knight's move apart.
+        let solved = super::Grid::from_str("625178943948326157371945862257619384463587291189432576792863415516294738834751629".to_string())
+            .with_constraint(Constraint::AntiKnight);
+        assert!(!solved.is_valid_solution());
+        assert!(!solved.is_consistent());
+        assert!(solved.clone().solve().is_err());
+
+        // The cells a knight's move away are peers, none of which share a unit with the center,
+        // while those of a corner lie within its block.
+        let grid = super::Grid::default().with_constraint(Constraint::AntiKnight);
+        assert_eq!(grid.peers(4, 4).count(), 28);
+        assert_eq!(grid.peers(0, 0).count(), 20);
+    }
+
+    #[test]
+    fn test_list_hidden_singles() {
+        let puzzle = super::Grid::from_str("000400002600000000003150800094000000000390050070006000400020001705000030102500400".to_string());
+        let solution = puzzle.solved().unwrap();
+
+        let singles = puzzle.list_hidden_singles();
+        assert!(!singles.is_empty());
+        assert!(singles.windows(2).all(|pair| (pair[0].2, pair[0].1) < (pair[1].2, pair[1].1)));
+        for &(digit, x, y) in &singles {
+            assert_eq!(solution.get(x, y), digit);
+            assert_eq!(puzzle.board()[x][y], 0);
+        }
+        assert_eq!(puzzle.filled_count(), puzzle.clue_count());
+
+        assert!(super::Grid::default().list_hidden_singles().is_empty());
+        assert!(super::Grid::from_str("660008940900006100070040000200610000000000200089002000000060005000000030800001600".to_string())
+            .list_hidden_singles().is_empty());
+    }
+
+    #[test]
+    fn test_guess_order() {
+        // The blank cells form a rectangle of two digits that may be swapped, the first of which is guessed.
+        let puzzle = super::Grid::from_str("625178943948320107371940802257619384463587291189432576792863415516294738834751629".to_string());
+        let first = "625178943948325167371946852257619384463587291189432576792863415516294738834751629";
+        let second = "625178943948326157371945862257619384463587291189432576792863415516294738834751629";
+
+        let mut grid = puzzle.clone();
+        assert!(grid.solve().is_ok());
+        assert_eq!(grid.to_str(), first);
+        assert_eq!(puzzle.solutions().map(|solution| solution.to_str()).collect::<Vec<_>>(), [first, second]);
+    }
+
+    #[test]
+    fn test_builder() {
+        use super::Constraint;
+
+        let puzzle = "000000000000000000000000001000000000000000000000030000001007060049000800020056090";
+        let solution = "916348572872165439354972681483629715195784326267531948531897264649213857728456193";
+        let plain = super::Grid::from_str(puzzle.to_string());
+        assert_eq!(plain.count_solutions(2), 2);
+        assert_eq!(plain.clone().with_constraint(Constraint::Diagonals).count_solutions(2), 2);
+        assert_eq!(plain.clone().with_constraint(Constraint::Windoku).count_solutions(2), 2);
+
+        // The puzzle is only unique when both constraints apply.
+        let mut grid = super::Grid::builder().diagonals().windoku().build();
+        assert_eq!(grid, super::Grid::default());
+        grid.load_str(puzzle).unwrap();
+        assert!(grid.has_unique_solution());
+        assert!(grid.solve().is_ok());
+        assert!(grid.verify_solution());
+        assert_eq!(grid.to_str(), solution);
+        assert_eq!(plain.with_constraint(Constraint::Windoku).with_constraint(Constraint::Diagonals).solved().unwrap(), grid);
+
+        // Each window is a unit, while the cells between the windows are not.
+        let grid = super::Grid::builder().windoku().build();
+        assert_eq!(grid.peers(1, 1).count(), 23);
+        assert_eq!(grid.peers(4, 4).count(), 20);
+
+        let standard = super::Grid::from_str("625178943948326157371945862257619384463587291189432576792863415516294738834751629".to_string());
+        assert!(!standard.with_constraint(Constraint::Windoku).is_valid_solution());
+        assert!(super::Grid::from_str(solution.to_string()).with_constraint(Constraint::Windoku).is_valid_solution());
+    }
+
+    #[test]
+    fn test_to_cnf() {
+        let cnf = super::Grid::default().to_cnf();
+        let mut lines = cnf.lines();
+        // 81 cells and 27 units by 9 digits, each with a clause for at least one and 36 for at most one.
+        assert_eq!(lines.next(), Some("p cnf 729 11988"));
+        assert_eq!(lines.next(), Some("1 2 3 4 5 6 7 8 9 0"));
+        assert_eq!(lines.next(), Some("-1 -2 0"));
+        assert_eq!(cnf.lines().count(), 11989);
+        assert!(cnf.lines().skip(1).all(|line| line.ends_with(" 0")));
+
+        let puzzle = "600008940900006100070040000200610000000000200089002000000060005000000030800001600";
+        let grid = super::Grid::from_str(puzzle.to_string());
+        let cnf = grid.to_cnf();
+        assert!(cnf.starts_with(&format!("p cnf 729 {}\n", 11988 + grid.clue_count())));
+        // The 6 in the first cell, and the 4 in the eighth cell of the first row.
+        assert!(cnf.lines().any(|line| line == "6 0"));
+        assert!(cnf.lines().any(|line| line == "67 0"));
+        assert_eq!(cnf.lines().filter(|line| !line.starts_with('-') && line.split(' ').count() == 2).count(), 22);
+    }
+
+    #[test]
+    fn test_from_sdk() {
+        let puzzle = "600008940900006100070040000200610000000000200089002000000060005000000030800001600";
+
+        let sdk = "#AAnonymous\n#DA puzzle with a comment\n[Puzzle]\n\
+            6....894.\n9....61..\n.7..4....\n2..61....\n......2..\n.89..2...\n....6...5\n.......3.\n8....16..\n";
+        assert_eq!(super::Grid::from_sdk(sdk).unwrap().to_str(), puzzle);
+
+        let ss = "6..|..8|94.\r\n9..|..6|1..\r\n.7.|.4.|...\r\n-----------\r\n2..|61.|...\r\n...|...|2..\r\n\
+            .89|..2|...\r\n-----------\r\n...|.6.|..5\r\n...|...|.3.\r\n8..|..1|6..\r\n";
+        assert_eq!(super::Grid::from_sdk(ss).unwrap().to_str(), puzzle);
+
+        assert_eq!(super::Grid::from_sdk("# only a comment\n"), Err(super::ParseError::InvalidLength(0)));
+        // Uncommented metadata is read as part of the grid.
+        assert_eq!(super::Grid::from_sdk(&sdk.replace("#D", "D")).unwrap_err(), super::ParseError::InvalidLength(101));
+    }
+
+    #[test]
+    fn test_hidden_single_unit() {
+        use super::{Step, UnitKind};
+
+        // 1 is possible in only the top-left cell of the top-left block, but elsewhere in its row and column.
+        let mut grid = super::Grid::from_str(
+            "080000000234000000567000000001000000000000000000000000000000000000000000000000000".to_string());
+        assert!(grid.settle().is_ok());
+        let step = grid.next_step();
+        assert_eq!(step, Some(Step::HiddenSingle { digit: 1, x: 0, y: 0, unit: UnitKind::Block }));
+        assert_eq!(step.unwrap().reason(), "this is the only cell in its block where the digit is possible");
+
+        let (digit, x, y) = (1, 2, 3);
+        let mut grid = super::Grid::default();
+        for cx in (0..9).filter(|&cx| cx != x) {
+            assert!(grid.apply_step(Step::Elimination { digit, x: cx, y }).is_ok());
+        }
+        let step = Step::HiddenSingle { digit, x, y, unit: UnitKind::Row };
+        assert_eq!(grid.next_step(), Some(step));
+    }
+
+    #[test]
+    fn test_likely_unique() {
+        let mut rng = rand_pcg::Pcg32::seed_from_u64(0);
+
+        let puzzle = super::Grid::from_str(
+            "000500000630000001000263749060900530000075000003008900040030000002050060500000010".to_string());
+        assert!(puzzle.has_unique_solution());
+        assert!(puzzle.likely_unique(8, &mut rng));
+        assert!(super::Grid::default().likely_unique(0, &mut rng));
+        assert!(!super::Grid::default().likely_unique(8, &mut rng));
+
+        let invalid = super::Grid::from_str(format!("11{}", "0".repeat(79)));
+        assert!(!invalid.likely_unique(8, &mut rng));
+    }
+
+    #[test]
+    fn test_is_solved() {
+        let mut grid = super::Grid::from_str(
+            "625178943948326157371945862257619384463587291189432576792863415516294738834751620".to_string());
+        assert!(!grid.is_solved());
+        assert!(grid.solve().is_ok());
+        assert!(grid.is_solved());
+        assert!(!super::Grid::default().is_solved());
+
+        let solution = "625178943948326157371945862257619384463587291189432576792863415516294738834751629";
+        assert!(super::Grid::from_str(solution.to_string()).is_solved());
+    }
+
+    #[test]
+    fn test_with_candidates() {
+        use super::ParseError;
+
+        // Leave each blank cell of a solved grid with its digit and one other as candidates.
+        let solution = super::Grid::from_str(
+            "625178943948326157371945862257619384463587291189432576792863415516294738834751629".to_string()).board();
+        let mut cells = solution;
+        let mut candidates = [[0u16; 9]; 9];
+        for y in 0..9 {
+            for x in 0..9 {
+                candidates[x][y] = 1 << (solution[x][y] - 1);
+                if (x + y) % 2 == 0 {
+                    cells[x][y] = 0;
+                    candidates[x][y] |= 1 << (solution[x][y] % 9);
+                }
+            }
+        }
+        let mut grid = super::Grid::with_candidates(cells, candidates).unwrap();
+        assert_eq!(grid.clue_count(), 40);
+        assert!(grid.solve().is_ok());
+        assert_eq!(grid.board(), solution);
+
+        // Candidates excluding the solution leave the grid without one.
+        candidates[0][0] = 1 << 8;
+        assert!(super::Grid::with_candidates(cells, candidates).unwrap().solve().is_err());
+
+        candidates[0][0] = 0;
+        assert_eq!(super::Grid::with_candidates(cells, candidates).unwrap_err(), ParseError::InvalidCandidates { x: 0, y: 0 });
+        candidates[0][0] = 1 << 9;
+        assert_eq!(super::Grid::with_candidates(cells, candidates).unwrap_err(), ParseError::InvalidCandidates { x: 0, y: 0 });
+        candidates[0][0] = 1 << 5;
+        candidates[1][0] = 1 << 3;
+        assert_eq!(super::Grid::with_candidates(cells, candidates).unwrap_err(), ParseError::InvalidCandidates { x: 1, y: 0 });
+    }
+
+    #[test]
+    fn test_step() {
+        use super::{StepResult, Technique};
+
+        let puzzle = "000500000630000001000263749060900530000075000003008900040030000002050060500000010";
+        let mut grid = super::Grid::from_str(puzzle.to_string());
+        assert_eq!(grid.step(), StepResult::Placed { digit: 1, x: 7, y: 8 });
+
+        let mut techniques = Vec::new();
+        let result = loop {
+            match grid.step() {
+                StepResult::Applied(technique) => techniques.push(technique),
+                StepResult::Eliminated { .. } | StepResult::Placed { .. } => (),
+                result => break result,
+            }
+        };
+        assert_eq!(result, StepResult::Solved);
+        assert!(techniques.contains(&Technique::HiddenSingles));
+        assert_eq!(grid.step(), StepResult::Solved);
+
+        let mut solution = super::Grid::from_str(puzzle.to_string());
+        assert!(solution.solve().is_ok());
+        assert_eq!(grid.to_str(), solution.to_str());
+
+        assert_eq!(super::Grid::default().step(), StepResult::NoProgress);
+        let mut invalid = super::Grid::from_str(format!("11{}", "0".repeat(79)));
+        let result = loop {
+            match invalid.step() {
+                StepResult::Placed { .. } | StepResult::Eliminated { .. } => (),
+                result => break result,
+            }
+        };
+        assert!(matches!(result, StepResult::Contradiction { digit: 1, .. }));
+    }
+
+    #[test]
+    fn test_add_thermo() {
+        let solution = "625178943948326157371945862257619384463587291189432576792863415516294738834751629";
+
+        // The 7s and 8s of the two rows may be swapped, unless the first row increases from left to right.
+        let mut puzzle = solution.to_string();
+        for i in [4, 5, 40, 41] {
+            puzzle.replace_range(i..i + 1, "0");
+        }
+        let mut grid = super::Grid::from_str(puzzle.clone());
+        assert_eq!(grid.count_solutions(3), 2);
+        grid.add_thermo(&[(4, 0), (5, 0)]);
+        assert!(grid.has_unique_solution());
+        assert!(grid.solve().is_ok());
+        assert!(grid.verify_solution());
+        assert_eq!(grid.to_str(), solution);
+
+        let mut grid = super::Grid::from_str(puzzle);
+        grid.add_thermo(&[(5, 0), (4, 0)]);
+        let mut rotated = grid.rotate90();
+        assert!(grid.solve().is_ok());
+        assert_eq!(&grid.to_str()[4..6], "87");
+        assert!(rotated.solve().is_ok());
+        assert_eq!(rotated.rotate90().rotate90().rotate90(), grid);
+
+        // Digits out of order contradict the thermometer.
+        let mut grid = super::Grid::from_str(solution.to_string());
+        grid.add_thermo(&[(1, 0), (2, 0), (3, 0)]);
+        assert!(!grid.is_valid_solution());
+        assert!(!grid.is_consistent());
+        assert!(grid.solve().is_err());
+
+        // A thermometer of every digit along an empty row leaves them in order, without guessing.
+        let mut grid = super::Grid::default();
+        grid.add_thermo(&core::array::from_fn::<_, 9, _>(|x| (x, 0)));
+        assert_eq!(grid.solve_logical(), super::SolveOutcome::Stuck);
+        assert_eq!(&grid.to_str()[..9], "123456789");
+
+        // Blank cells need digits left to fill them.
+        let mut grid = super::Grid::from_str(format!("0090{}", "0".repeat(77)));
+        assert!(grid.is_consistent());
+        grid.add_thermo(&[(0, 0), (1, 0), (2, 0), (3, 0)]);
+        assert!(!grid.is_consistent());
+        assert!(grid.solve().is_err());
+    }
+
+    #[test]
+    fn test_to_str_with_blank() {
+        extern crate std;
+
+        let puzzle = "000500000630000001000263749060900530000075000003008900040030000002050060500000010";
+        let grid = super::Grid::from_str(puzzle.to_string());
+        let dotted = grid.to_str_with_blank('.');
+        assert_eq!(dotted, puzzle.replace('0', "."));
+        assert_eq!(super::Grid::from_str_with_blank(&dotted, '.').unwrap().to_str(), puzzle);
+        assert_eq!(grid.to_str_with_blank('0'), grid.to_str());
+
+        assert!(std::panic::catch_unwind(|| grid.to_str_with_blank('5')).is_err());
+        let grid = super::Grid16::default();
+        assert!(std::panic::catch_unwind(|| grid.to_str_with_blank('g')).is_err());
+        assert_eq!(grid.to_str_with_blank('.'), ".".repeat(256));
+
+        // Digits beyond those of a small grid are still digits, so cannot stand for its blank cells.
+        let grid = super::Grid4::default();
+        assert!(std::panic::catch_unwind(|| grid.to_str_with_blank('7')).is_err());
+        assert_eq!(grid.to_str_with_blank('0'), "0".repeat(16));
+        assert_eq!(grid.to_str_with_blank('h'), "h".repeat(16));
+    }
+
+    #[test]
+    fn test_first_empty() {
+        use alloc::string::String;
+
+        let puzzle = "625178943948326157371945862257619384463587291189432576792863415516294738834751629";
+        assert_eq!(super::Grid::from_str(puzzle.to_string()).first_empty(), None);
+        assert_eq!(super::Grid::default().first_empty(), Some((0, 0)));
+        let grid = super::Grid::from_str(puzzle.replacen("948", "900", 1));
+        assert_eq!(grid.first_empty(), Some((1, 1)));
+
+        // Search for a solution externally, trying each candidate of the first blank cell.
+        let blank = (0..81).map(|i| if i < 9 || i % 9 == 0 { b'0' } else { puzzle.as_bytes()[i] }).collect();
+        let mut grid = super::Grid::from_str(String::from_utf8(blank).unwrap());
+        grid.recompute_candidates();
+        let mut pending = alloc::vec![grid];
+        let solution = loop {
+            let grid = pending.pop().unwrap();
+            let Some((x, y)) = grid.first_empty() else { break grid };
+            for digit in grid.candidate_digits(x, y) {
+                let mut guess = grid.clone();
+                if guess.place(x, y, digit).is_ok() {
+                    pending.push(guess);
+                }
+            }
+        };
+        assert!(solution.is_valid_solution());
+        assert_eq!(solution.to_str(), puzzle);
+    }
+
+    #[test]
+    fn test_solve_bounded() {
+        use super::SolveBudgetError;
+
+        // Solved by logic alone, taking a single step.
+        let puzzle = "000500000630000001000263749060900530000075000003008900040030000002050060500000010";
+        let mut grid = super::Grid::from_str(puzzle.to_string());
+        assert_eq!(grid.clone().solve_bounded(0), Err(SolveBudgetError::Exhausted));
+        assert!(grid.solve_bounded(1).is_ok());
+        assert!(grid.verify_solution());
+
+        // Requires guessing, with each guess taking a step alongside the round of propagation that follows it.
+        let puzzle = "800000000003600000070090200050007000000045700000100030001000068008500010090000400";
+        let mut grid = super::Grid::from_str(puzzle.to_string());
+        let guesses = grid.clone().solve_with_stats().unwrap().guesses;
+        assert_eq!(grid.clone().solve_bounded(2 * guesses), Err(SolveBudgetError::Exhausted));
+        assert!(grid.solve_bounded(2 * guesses + 1).is_ok());
+        assert!(grid.verify_solution());
+
+        let mut invalid = super::Grid::from_str(format!("11{}", "0".repeat(79)));
+        assert!(matches!(invalid.solve_bounded(usize::MAX), Err(SolveBudgetError::Contradiction { digit: 1, .. })));
+    }
+
+    #[test]
+    fn test_units() {
+        let solution = "625178943948326157371945862257619384463587291189432576792863415516294738834751629";
+        let grid = super::Grid::from_str(solution.to_string());
+        assert_eq!(grid.row(0), [6, 2, 5, 1, 7, 8, 9, 4, 3]);
+        assert_eq!(grid.row(8), [8, 3, 4, 7, 5, 1, 6, 2, 9]);
+        assert_eq!(grid.col(0), [6, 9, 3, 2, 4, 1, 7, 5, 8]);
+        assert_eq!(grid.col(8), [3, 7, 2, 4, 1, 6, 5, 8, 9]);
+        assert_eq!(grid.block(0), [6, 2, 5, 9, 4, 8, 3, 7, 1]);
+        assert_eq!(grid.block(5), [3, 8, 4, 2, 9, 1, 5, 7, 6]);
+        assert_eq!(grid.block(8), [4, 1, 5, 7, 3, 8, 6, 2, 9]);
+        for i in 0..9 {
+            assert_eq!(grid.block(i).iter().fold(0u16, |seen, &digit| seen | 1 << digit), 0b1111111110);
+        }
+
+        let grid4 = super::Grid4::try_from_str("1234341221434321").unwrap().solved().unwrap();
+        assert_eq!(grid4.block(1), [3, 4, 1, 2]);
+        assert_eq!(grid4.block(2), [2, 1, 4, 3]);
+    }
+
+    #[test]
+    fn test_send_sync() {
+        // Fails to compile should a field ever stop grids from being used across threads.
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<super::Grid>();
+        assert_send_sync::<super::Grid4>();
+        assert_send_sync::<super::Grid16>();
+        assert_send_sync::<super::Solutions>();
+        assert_send_sync::<super::GridBuilder>();
+    }
+
+    #[test]
+    fn test_rectangular_blocks() {
+        use super::{Constraint, Grid6, Grid8, Grid12};
+
+        let mut grid = Grid6::default();
+        assert!(grid.solve().is_ok());
+        assert!(grid.verify_solution());
+        assert_eq!(grid.to_str(), "123456456123231564564231312645645312");
+        assert_eq!(grid.block(1), [4, 5, 6, 1, 2, 3]);
+        assert_eq!(grid.block(2), [2, 3, 1, 5, 6, 4]);
+        assert_eq!(
+            grid.to_string(),
+            "+---+---+\n|123|456|\n|456|123|\n+---+---+\n|231|564|\n|564|231|\n+---+---+\n|312|645|\n|645|312|\n+---+---+"
+        );
+
+        let mut rng = rand_pcg::Pcg32::seed_from_u64(0);
+        let puzzle = Grid6::generate(10, &mut rng);
+        assert!(puzzle.has_unique_solution());
+        assert!(puzzle.solved().unwrap().verify_solution());
+
+        let mut grid = Grid8::default();
+        assert!(grid.solve().is_ok());
+        assert!(grid.verify_solution());
+        assert_eq!(grid.block(2), [2, 4, 1, 3, 6, 8, 5, 7]);
+        let mut grid = Grid12::default();
+        assert!(grid.solve().is_ok());
+        assert!(grid.verify_solution());
+        assert_eq!(grid.block(1), [5, 6, 7, 8, 9, 10, 11, 12, 1, 2, 3, 4]);
+
+        let mut grid = Grid6::default().with_constraint(Constraint::Windoku);
+        assert!(grid.solve().is_ok());
+        assert!(grid.verify_solution());
+    }
+
+    #[test]
+    fn test_explain() {
+        use super::{SolveError, Step, Technique};
+
+        let puzzle = super::Grid::from_str("005000080000709004000004503002010000450000870000300000000570300178006000000000020".to_string());
+        let steps = puzzle.explain().unwrap();
+        assert!(steps.contains(&Step::Placement { digit: 5, x: 2, y: 0 }));
+        assert!(steps.iter().any(|step| matches!(step, Step::TechniqueElimination { technique: Technique::NakedPairs, .. })));
+
+        // Taking the steps in order solves the puzzle, which is itself left untouched.
+        let mut grid = puzzle.clone();
+        for &step in &steps {
+            assert!(grid.apply_step(step).is_ok());
+        }
+        assert_eq!(grid.board(), puzzle.solved().unwrap().board());
+        assert_eq!(puzzle.next_step(), Some(steps[0]));
+
+        let guessing = super::Grid::from_str("800000320007000600009274800000705006050040008600000000000916000000500000100000035".to_string());
+        assert_eq!(guessing.explain(), Err(SolveError::Stuck));
+        let contradiction = super::Grid::from_str(format!("11{}", "0".repeat(79)));
+        assert!(matches!(contradiction.explain(), Err(SolveError::Contradiction { .. })));
+    }
+
+    #[test]
+    fn test_add_inequality() {
+        let solution = "625178943948326157371945862257619384463587291189432576792863415516294738834751629";
+
+        // The 7s and 8s of the two columns may be swapped, unless the upper cell of one is the lesser.
+        let mut puzzle = solution.to_string();
+        for i in [4, 5, 40, 41] {
+            puzzle.replace_range(i..i + 1, "0");
+        }
+        let mut grid = super::Grid::from_str(puzzle.clone());
+        grid.add_inequality((4, 0), (4, 4));
+        assert!(grid.has_unique_solution());
+        assert!(grid.solve().is_ok());
+        assert_eq!(grid.to_str(), solution);
+
+        let mut grid = super::Grid::from_str(puzzle);
+        grid.add_inequality((4, 4), (4, 0));
+        assert!(grid.solve().is_ok());
+        assert_eq!(&grid.to_str()[4..6], "87");
+
+        // The lesser cell cannot hold the highest digit, nor the greater the lowest.
+        let mut grid = super::Grid::default();
+        grid.add_inequality((0, 0), (1, 0));
+        assert_eq!(grid.solve_logical(), super::SolveOutcome::Stuck);
+        assert_eq!(grid.candidate_digits(0, 0).collect::<Vec<_>>(), [1, 2, 3, 4, 5, 6, 7, 8]);
+        assert_eq!(grid.candidate_digits(1, 0).collect::<Vec<_>>(), [2, 3, 4, 5, 6, 7, 8, 9]);
+
+        let mut grid = super::Grid::from_str(solution.to_string());
+        grid.add_inequality((0, 0), (1, 0));
+        assert!(!grid.is_valid_solution());
+    }
+
+    #[test]
+    fn test_pending_eliminations() {
+        let puzzle = "000500000630000001000263749060900530000075000003008900040030000002050060500000010";
+        let grid = super::Grid::from_str(puzzle.to_string());
+        let board = grid.board();
+
+        // Each candidate of a blank cell is ruled out exactly when a peer holds the digit.
+        let mut expected = Vec::new();
+        for y in 0..9 {
+            for x in 0..9 {
+                for digit in (1..=9).filter(|_| board[x][y] == 0) {
+                    let peer = (0..9).any(|i| board[i][y] == digit || board[x][i] == digit
+                        || board[x / 3 * 3 + i % 3][y / 3 * 3 + i / 3] == digit);
+                    if peer {
+                        expected.push((digit, x, y));
+                    }
+                }
+            }
+        }
+        let eliminations = grid.pending_eliminations();
+        assert_eq!(eliminations, expected);
+        assert!(eliminations.contains(&(5, 0, 0)));
+        assert_eq!(grid.next_step(), Some(super::Step::Placement { digit: 1, x: 7, y: 8 }));
+
+        let mut solved = grid.clone();
+        assert_eq!(solved.solve_logical(), super::SolveOutcome::Solved);
+        assert!(solved.pending_eliminations().is_empty());
+
+        let contradiction = super::Grid::from_str(format!("11{}", "0".repeat(79)));
+        assert!(contradiction.pending_eliminations().is_empty());
+    }
+
+    #[test]
+    fn test_counter_underflow() {
+        use super::SolveError;
+
+        // Counters out of step with the candidates are reported as a contradiction, leaving the cell untouched.
+        let mut candidates = [[super::Grid::MAYBE_ALL; 9]; 9];
+        candidates[0][0] &= !1;
+        let mut grid = super::Grid::with_candidates([[0; 9]; 9], candidates).unwrap();
+        grid.row_digit_counters[0][0] = 0;
+        assert_eq!(grid.solve(), Err(SolveError::Contradiction { x: 0, y: 0, digit: 1 }));
+        assert_eq!(grid.candidates(0, 0), super::Grid::MAYBE_ALL);
+        assert_eq!(grid.col_digit_counters[0][0], 9);
+        assert!(grid.trail.is_empty());
+
+        // Setting a cell reports the digit placed there, not the candidate being cleared whose counter was out of step.
+        let mut cells = [[0; 9]; 9];
+        cells[0][0] = 1;
+        let mut candidates = [[super::Grid::MAYBE_ALL; 9]; 9];
+        candidates[0][0] = 0;
+        let mut grid = super::Grid::with_candidates(cells, candidates).unwrap();
+        grid.row_digit_counters[0][1] = 0;
+        assert_eq!(grid.solve(), Err(SolveError::Contradiction { x: 0, y: 0, digit: 1 }));
+    }
+
+    #[test]
+    fn test_cage_count() {
+        // Every cell of a sixteen by sixteen grid may be a cage of its own, the last of which is kept apart from the rest.
+        let solution = super::Grid16::default().solved().unwrap();
+        let mut grid = super::Grid16::default();
+        for y in 0..16 {
+            for x in 0..16 {
+                grid.add_cage(&[(x, y)], solution.get(x, y));
+            }
+        }
+        assert_eq!(grid.cage_of[15][15], 256);
+        assert_eq!(grid.solve_logical(), super::SolveOutcome::Solved);
+        assert_eq!(grid, solution);
+    }
+
+    #[test]
+    fn test_cage_combos() {
+        // The sets of digits stepped through match those of every bitmap with a digit per cell adding up to the sum.
+        let cells = [(0, 0), (1, 0), (2, 0), (3, 0), (4, 0), (5, 0), (6, 0), (7, 0), (8, 0)];
+        for len in 1..=9 {
+            for sum in 0..=46 {
+                let cage = super::Cage::<9> { cells: super::List::from_slice(&cells[..len]), sum };
+                let expected = (0..=super::Grid::MAYBE_ALL)
+                    .filter(|combo| combo.count_ones() as usize == len)
+                    .filter(|combo| (0..9).filter(|di| combo & 1 << di != 0).map(|di| di + 1).sum::<usize>() == sum)
+                    .collect::<Vec<_>>();
+                assert_eq!(cage.combos().collect::<Vec<_>>(), expected);
+            }
+        }
     }
 }
@@ -1,25 +1,123 @@
-use sudoku_solver::Grid;
+use std::process::ExitCode;
+use sudoku_solver::{Grid, SolveError};
 
-fn main() {
+/// Exit code when the grid has no solution.
+const EXIT_NO_SOLUTION: u8 = 1;
+/// Exit code when no valid grid was given.
+const EXIT_INVALID_INPUT: u8 = 2;
+/// Exit code when the grid has more than one solution, one of which is still printed.
+const EXIT_NOT_UNIQUE: u8 = 3;
+
+fn main() -> ExitCode {
     let mut grid = None;
-    for arg in std::env::args() {
-        if arg.trim().trim_start_matches('-').len() == 81 {
-            grid = Some(Grid::from_str(arg));
+    let mut pretty = false;
+    let mut file = None;
+    let mut unique = false;
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--pretty" {
+            pretty = true;
+        } else if arg == "--file" {
+            file = args.next();
+        } else if arg == "--unique" {
+            unique = true;
+        } else if arg.trim().trim_start_matches('-').len() == 81 {
+            grid = Some(Grid::try_from_str(&arg));
         }
     }
 
-    if let Some(mut g) = grid {
-        if let Err(_) = g.solve() {
-            println!("No solution could be found.");
-            return;
-        }
-        if !g.verify_solution() {
-            panic!("SOLUTION FOUND WAS INVALID, THIS IS LIKELY A BUG.");
+    if let Some(path) = file {
+        return solve_file(&path, unique);
+    }
+
+    let grid = grid.unwrap_or_else(|| {
+        // No grid was given as an argument, read either a single line or a block of lines from stdin instead.
+        let input = std::io::read_to_string(std::io::stdin()).unwrap_or_default();
+        input.parse::<Grid>()
+    });
+
+    let mut g = match grid {
+        Ok(g) => g,
+        Err(err) => {
+            eprintln!("Valid grid string argument not found: {}.", err);
+            return ExitCode::from(EXIT_INVALID_INPUT);
         }
+    };
 
-        print!("Solution: {}", g.to_str());
+    let puzzle = g.clone();
+    let unique = match g.solve_checked() {
+        Ok(()) => true,
+        Err(SolveError::NotUnique) => {
+            eprintln!("The puzzle has more than one solution, printing one of them.");
+            false
+        },
+        Err(err) => {
+            eprintln!("No solution could be found: {}.", err);
+            return ExitCode::from(EXIT_NO_SOLUTION);
+        },
+    };
+    if !g.verify_solution() {
+        panic!("SOLUTION FOUND WAS INVALID, THIS IS LIKELY A BUG.");
+    }
+
+    if pretty {
+        println!("Puzzle:\n{}\n\nSolution:\n{}", puzzle, g);
     } else {
-        println!("Valid grid string argument not found.");
+        print!("Solution: {}", g.to_str());
     }
+    if unique { ExitCode::SUCCESS } else { ExitCode::from(EXIT_NOT_UNIQUE) }
 }
 
+fn solve_file(path: &str, unique: bool) -> ExitCode {
+    //! Solve each line of the file as a puzzle, printing a line per solution followed by a summary.
+    //! 
+    //! Only if `unique` is set is each puzzle checked for other solutions, which costs a full search of each.
+
+    let input = match std::fs::read_to_string(path) {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("Could not read {}: {}.", path, err);
+            return ExitCode::from(EXIT_INVALID_INPUT);
+        }
+    };
+
+    let start = std::time::Instant::now();
+    let mut grid = Grid::default();
+    let (mut solved, mut not_unique, mut failed) = (0, 0, 0);
+    for line in input.lines().filter(|line| !line.trim().is_empty()) {
+        if let Err(err) = grid.load_str(line) {
+            println!("error: invalid input, {}", err);
+            failed += 1;
+            continue;
+        }
+        let result = if unique { grid.solve_checked() } else { grid.solve() };
+        match result {
+            Ok(()) if grid.verify_solution() => {
+                println!("{}", grid.to_str());
+                solved += 1;
+            },
+            // The grid is left holding one of its solutions, which is printed all the same.
+            Err(SolveError::NotUnique) if grid.verify_solution() => {
+                println!("{}", grid.to_str());
+                not_unique += 1;
+            },
+            Ok(()) | Err(SolveError::NotUnique) => {
+                println!("error: no solution could be found");
+                failed += 1;
+            },
+            Err(err) => {
+                println!("error: {}", err);
+                failed += 1;
+            },
+        }
+    }
+
+    eprintln!("Solved: {}, not unique: {}, failed: {}, total time: {:?}.", solved, not_unique, failed, start.elapsed());
+    if failed != 0 {
+        ExitCode::from(EXIT_NO_SOLUTION)
+    } else if not_unique != 0 {
+        ExitCode::from(EXIT_NOT_UNIQUE)
+    } else {
+        ExitCode::SUCCESS
+    }
+}
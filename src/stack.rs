@@ -0,0 +1,364 @@
+//! Stacks of the solver's pending operations, changes, and guesses, and lists of the grid's constraints.
+//!
+//! The stacks are backed by `Vec` by default. With the `fixed-stacks` feature, they are instead stored inline
+//! with a capacity that is sufficient for the size of the grid, such that solving never allocates. The lists are
+//! likewise backed by `Vec` unless the `alloc` feature is disabled, such that grids need no heap at all.
+
+#[cfg(any(not(feature = "fixed-stacks"), feature = "alloc"))]
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(not(feature = "alloc"))]
+use core::mem::MaybeUninit;
+use core::ops::{Deref, DerefMut};
+
+/// A pending operation of the solver, as a digit and the index of the cell it applies to.
+pub(crate) type Op = (usize, (usize, usize));
+
+/// Stack of pending operations, holding each distinct operation at most once with `fixed-stacks`.
+#[cfg(not(feature = "fixed-stacks"))]
+pub(crate) type OpStack<const N: usize> = Stack<Op, N, N, N>;
+
+/// A stack of up to `A * B * C` items, growing as needed after reserving space for `A * B` items up front.
+#[cfg(not(feature = "fixed-stacks"))]
+#[derive(Clone)]
+pub(crate) struct Stack<T, const A: usize, const B: usize, const C: usize> {
+    items: Vec<T>,
+}
+
+/// A stack of up to `A * B * C` items, stored inline.
+#[cfg(feature = "fixed-stacks")]
+#[derive(Clone)]
+pub(crate) struct Stack<T: Packed, const A: usize, const B: usize, const C: usize> {
+    items: [[[T::Packed; A]; B]; C],
+    len: usize,
+}
+
+#[cfg(not(feature = "fixed-stacks"))]
+impl<T: Copy, const A: usize, const B: usize, const C: usize> Stack<T, A, B, C> {
+    pub(crate) fn new() -> Self {
+        Self { items: Vec::with_capacity(A * B) }
+    }
+    /// Returns an empty stack that reserves no space until the first push, such that unused stacks never allocate.
+    pub(crate) fn unreserved() -> Self {
+        Self { items: Vec::new() }
+    }
+
+    pub(crate) fn push(&mut self, item: T) {
+        self.items.push(item);
+    }
+    pub(crate) fn pop(&mut self) -> Option<T> {
+        self.items.pop()
+    }
+    pub(crate) fn last(&self) -> Option<T> {
+        self.items.last().copied()
+    }
+    pub(crate) fn clear(&mut self) {
+        self.items.clear();
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.items.len()
+    }
+    pub(crate) fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+    #[cfg(all(test, feature = "alloc"))]
+    pub(crate) fn capacity(&self) -> usize {
+        self.items.capacity()
+    }
+    pub(crate) fn iter(&self) -> impl DoubleEndedIterator<Item = T> + '_ {
+        self.items.iter().copied()
+    }
+}
+
+#[cfg(feature = "fixed-stacks")]
+impl<T: Packed, const A: usize, const B: usize, const C: usize> Stack<T, A, B, C> {
+    pub(crate) fn new() -> Self {
+        Self { items: [[[T::Packed::default(); A]; B]; C], len: 0 }
+    }
+    pub(crate) fn unreserved() -> Self {
+        Self::new()
+    }
+
+    pub(crate) fn push(&mut self, item: T) {
+        let slot = self.items.as_flattened_mut().as_flattened_mut().get_mut(self.len)
+            .expect("stack capacity exceeded, this is likely a bug.");
+        *slot = item.pack();
+        self.len += 1;
+    }
+    pub(crate) fn pop(&mut self) -> Option<T> {
+        let item = self.last()?;
+        self.len -= 1;
+        Some(item)
+    }
+    pub(crate) fn last(&self) -> Option<T> {
+        self.iter().next_back()
+    }
+    pub(crate) fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+    pub(crate) fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    #[cfg(all(test, feature = "alloc"))]
+    pub(crate) fn capacity(&self) -> usize {
+        A * B * C
+    }
+    pub(crate) fn iter(&self) -> impl DoubleEndedIterator<Item = T> + '_ {
+        self.items.as_flattened().as_flattened()[..self.len].iter().map(|&packed| T::unpack(packed))
+    }
+}
+
+#[cfg(not(feature = "fixed-stacks"))]
+impl<T: Copy + fmt::Debug, const A: usize, const B: usize, const C: usize> fmt::Debug for Stack<T, A, B, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(feature = "fixed-stacks")]
+impl<T: Packed + fmt::Debug, const A: usize, const B: usize, const C: usize> fmt::Debug for Stack<T, A, B, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+/// Stack of pending operations, holding each distinct operation at most once.
+///
+/// Pushing an operation that is already pending has no effect, as the solver would only repeat it.
+/// As there are `N` digits for each of the `N * N` cells, at most `N * N * N` operations are ever held.
+#[cfg(feature = "fixed-stacks")]
+#[derive(Clone)]
+pub(crate) struct OpStack<const N: usize> {
+    ops: Stack<Op, N, N, N>,
+    /// Bitmaps of the digits with pending operations per cell.
+    pending: [[u16; N]; N],
+}
+
+#[cfg(feature = "fixed-stacks")]
+impl<const N: usize> OpStack<N> {
+    pub(crate) fn new() -> Self {
+        Self { ops: Stack::new(), pending: [[0; N]; N] }
+    }
+
+    pub(crate) fn push(&mut self, op: Op) {
+        let (digit, (x, y)) = op;
+        if self.pending[x][y] & 1 << (digit - 1) == 0 {
+            self.pending[x][y] |= 1 << (digit - 1);
+            self.ops.push(op);
+        }
+    }
+    pub(crate) fn pop(&mut self) -> Option<Op> {
+        let (digit, (x, y)) = self.ops.pop()?;
+        self.pending[x][y] &= !(1 << (digit - 1));
+        Some((digit, (x, y)))
+    }
+    pub(crate) fn last(&self) -> Option<Op> {
+        self.ops.last()
+    }
+    pub(crate) fn clear(&mut self) {
+        self.ops.clear();
+        self.pending = [[0; N]; N];
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+    #[cfg(all(test, feature = "alloc"))]
+    pub(crate) fn capacity(&self) -> usize {
+        self.ops.capacity()
+    }
+    pub(crate) fn iter(&self) -> impl DoubleEndedIterator<Item = Op> + '_ {
+        self.ops.iter()
+    }
+}
+
+#[cfg(feature = "fixed-stacks")]
+impl<const N: usize> fmt::Debug for OpStack<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.ops.fmt(f)
+    }
+}
+
+/// A value that can be stored compactly within a fixed-capacity stack.
+#[cfg(feature = "fixed-stacks")]
+pub(crate) trait Packed: Copy {
+    /// The compact representation, holding indices and digits as bytes.
+    type Packed: Copy + Default;
+
+    fn pack(self) -> Self::Packed;
+    fn unpack(packed: Self::Packed) -> Self;
+}
+
+#[cfg(feature = "fixed-stacks")]
+impl Packed for Op {
+    type Packed = [u8; 3];
+
+    fn pack(self) -> [u8; 3] {
+        let (digit, (x, y)) = self;
+        [digit as u8, x as u8, y as u8]
+    }
+    fn unpack([digit, x, y]: [u8; 3]) -> Self {
+        (digit as usize, (x as usize, y as usize))
+    }
+}
+
+#[cfg(feature = "fixed-stacks")]
+impl Packed for crate::Change {
+    /// The digit of the removed maybe, or zero if the cell was set, followed by the index of the cell.
+    type Packed = [u8; 3];
+
+    fn pack(self) -> [u8; 3] {
+        match self {
+            crate::Change::SetCell((x, y)) => [0, x as u8, y as u8],
+            crate::Change::DelMaybe(digit, (x, y)) => [digit as u8, x as u8, y as u8],
+        }
+    }
+    fn unpack([digit, x, y]: [u8; 3]) -> Self {
+        match digit {
+            0 => crate::Change::SetCell((x as usize, y as usize)),
+            _ => crate::Change::DelMaybe(digit as usize, (x as usize, y as usize)),
+        }
+    }
+}
+
+#[cfg(feature = "fixed-stacks")]
+impl Packed for crate::Guess {
+    /// The trail length, which is less than `17 * N * N`, the index of the cell, and the untried possibilities.
+    type Packed = [u16; 3];
+
+    fn pack(self) -> [u16; 3] {
+        let (checkpoint, (x, y), maybes) = self;
+        [checkpoint as u16, (x << 8 | y) as u16, maybes]
+    }
+    fn unpack([checkpoint, index, maybes]: [u16; 3]) -> Self {
+        (checkpoint as usize, (index as usize >> 8, index as usize & 0xff), maybes)
+    }
+}
+
+/// A list of up to `A * B` items, such as the constraints of a grid, dereferencing to a slice of them.
+#[cfg(feature = "alloc")]
+#[derive(Clone)]
+pub(crate) struct List<T, const A: usize, const B: usize> {
+    items: Vec<T>,
+}
+
+/// A list of up to `A * B` items, stored inline.
+#[cfg(not(feature = "alloc"))]
+#[derive(Clone, Copy)]
+pub(crate) struct List<T: Copy, const A: usize, const B: usize> {
+    /// The items, of which the first `len` are initialized.
+    items: [[MaybeUninit<T>; A]; B],
+    len: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Clone, const A: usize, const B: usize> List<T, A, B> {
+    pub(crate) fn new() -> Self {
+        Self { items: Vec::new() }
+    }
+    pub(crate) fn from_slice(items: &[T]) -> Self {
+        Self { items: items.to_vec() }
+    }
+
+    pub(crate) fn push(&mut self, item: T) {
+        self.items.push(item);
+    }
+    pub(crate) fn clear(&mut self) {
+        self.items.clear();
+    }
+}
+
+#[cfg(not(feature = "alloc"))]
+impl<T: Copy, const A: usize, const B: usize> List<T, A, B> {
+    pub(crate) fn new() -> Self {
+        Self { items: [[MaybeUninit::uninit(); A]; B], len: 0 }
+    }
+    pub(crate) fn from_slice(items: &[T]) -> Self {
+        let mut list = Self::new();
+        for &item in items {
+            list.push(item);
+        }
+        list
+    }
+
+    pub(crate) fn push(&mut self, item: T) {
+        let slot = self.items.as_flattened_mut().get_mut(self.len)
+            .expect("list capacity exceeded, this is likely a bug.");
+        slot.write(item);
+        self.len += 1;
+    }
+    pub(crate) fn clear(&mut self) {
+        self.len = 0;
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T, const A: usize, const B: usize> Deref for List<T, A, B> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.items
+    }
+}
+#[cfg(feature = "alloc")]
+impl<T, const A: usize, const B: usize> DerefMut for List<T, A, B> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        &mut self.items
+    }
+}
+
+#[cfg(not(feature = "alloc"))]
+impl<T: Copy, const A: usize, const B: usize> Deref for List<T, A, B> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        let items = &self.items.as_flattened()[..self.len];
+        // SAFETY: the first `len` items have been written by `push`, and `MaybeUninit<T>` has the layout of `T`.
+        unsafe { &*(items as *const [MaybeUninit<T>] as *const [T]) }
+    }
+}
+#[cfg(not(feature = "alloc"))]
+impl<T: Copy, const A: usize, const B: usize> DerefMut for List<T, A, B> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        let items = &mut self.items.as_flattened_mut()[..self.len];
+        // SAFETY: as for `deref`.
+        unsafe { &mut *(items as *mut [MaybeUninit<T>] as *mut [T]) }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T, const A: usize, const B: usize> IntoIterator for &'a List<T, A, B> {
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+#[cfg(not(feature = "alloc"))]
+impl<'a, T: Copy, const A: usize, const B: usize> IntoIterator for &'a List<T, A, B> {
+    type Item = &'a T;
+    type IntoIter = core::slice::Iter<'a, T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: fmt::Debug, const A: usize, const B: usize> fmt::Debug for List<T, A, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.items.fmt(f)
+    }
+}
+#[cfg(not(feature = "alloc"))]
+impl<T: Copy + fmt::Debug, const A: usize, const B: usize> fmt::Debug for List<T, A, B> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
@@ -0,0 +1,111 @@
+//! Counts the heap allocations made while solving, which requires a global allocator of its own.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use sudoku_solver::{Grid, SolverConfig};
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations() -> usize {
+    ALLOCATIONS.with(Cell::get)
+}
+
+/// A 17-clue puzzle, the fewest clues a puzzle with a unique solution can have.
+const SEVENTEEN_CLUES: &str = "000000010400000000020000000000050407008000300001090000300400200050100000000806000";
+
+#[test]
+fn test_logical_solves_do_not_allocate() {
+    let puzzles = [
+        "000500000630000001000263749060900530000075000003008900040030000002050060500000010",
+        "000400002600000000003150800094000000000390050070006000400020001705000030102500400",
+        "050600940000800000007902003705000010090006000040309020100000438000000000002000590",
+        "400001309000000006010800000000040000005000070000209004068900500200007080500000001",
+    ];
+
+    // The first solve grows the grid's stacks as needed, after which they are reused.
+    let mut grid = Grid::default();
+    for puzzle in puzzles {
+        grid.load_str(puzzle).unwrap();
+        grid.solve().unwrap();
+    }
+
+    for _ in 0..10 {
+        for puzzle in puzzles {
+            let before = allocations();
+            grid.load_str(puzzle).unwrap();
+            let stats = grid.solve_with_stats().unwrap();
+            assert_eq!(stats.guesses, 0);
+            assert_eq!(allocations(), before, "solving {} allocated", puzzle);
+            assert!(grid.verify_solution());
+        }
+    }
+}
+
+#[cfg(feature = "fixed-stacks")]
+#[test]
+fn test_guessing_solves_do_not_allocate_with_fixed_stacks() {
+    // With fixed stacks, even the guesses are held inline, such that no solve allocates once the grid exists.
+    let mut grid = Grid::default();
+    for puzzle in [
+        "800000320007000600009274800000705006050040008600000000000916000000500000100000035",
+        "000000012000000003002300400001800005060070800000009000008500000900040500470006000",
+    ] {
+        let before = allocations();
+        grid.load_str(puzzle).unwrap();
+        let stats = grid.solve_with_stats().unwrap();
+        assert!(stats.guesses > 0);
+        assert_eq!(allocations(), before, "solving {} allocated", puzzle);
+        assert!(grid.verify_solution());
+    }
+}
+
+#[cfg(not(feature = "alloc"))]
+#[test]
+fn test_grids_do_not_allocate_without_alloc() {
+    // Without `alloc`, the constraints are held inline too, such that not even building the grid allocates.
+    let before = allocations();
+    let mut grid = Grid::default().with_constraint(sudoku_solver::Constraint::Diagonals);
+    grid.add_cage(&[(0, 0), (1, 0)], 3);
+    grid.add_thermo(&[(0, 8), (1, 8), (2, 8)]);
+    grid.solve().unwrap();
+    assert_eq!(allocations(), before);
+    assert!(grid.verify_solution());
+}
+
+#[test]
+fn test_guesses_are_undone_without_allocating() {
+    // Without techniques, the 17-clue puzzle takes thousands of guesses, which are undone in place when they fail,
+    // such that only the stack of guesses itself may grow as the search deepens.
+    let config = SolverConfig::new().without_techniques();
+    let mut grid = Grid::default();
+    grid.load_str(SEVENTEEN_CLUES).unwrap();
+    grid.solve_with(&config).unwrap();
+
+    let before = allocations();
+    grid.load_str(SEVENTEEN_CLUES).unwrap();
+    grid.solve_with(&config).unwrap();
+    let allocated = allocations() - before;
+    assert!(allocated <= 8, "solving {} allocated {} times", SEVENTEEN_CLUES, allocated);
+    assert!(grid.verify_solution());
+}
@@ -0,0 +1,41 @@
+//! Property tests of the invariants that hold across parsing, solving, and formatting generated puzzles.
+#![cfg(feature = "alloc")]
+
+use proptest::prelude::*;
+use rand_core::SeedableRng;
+use sudoku_solver::Grid;
+
+/// Generates puzzles with a unique solution from a random seed and clue count.
+fn puzzles() -> impl Strategy<Value = Grid> {
+    (any::<u64>(), 25..=45usize).prop_map(|(seed, clues)| {
+        Grid::generate(clues, &mut rand_pcg::Pcg32::seed_from_u64(seed))
+    })
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn solved_grids_are_valid_solutions(puzzle in puzzles()) {
+        let mut grid = puzzle.clone();
+        prop_assert!(grid.solve().is_ok());
+        prop_assert!(grid.is_valid_solution());
+        prop_assert!(grid.verify_solution());
+
+        // The givens of the puzzle are kept by its solution.
+        for (given, digit) in puzzle.to_str().chars().zip(grid.to_str().chars()) {
+            prop_assert!(given == '0' || given == digit);
+        }
+    }
+
+    #[test]
+    fn strings_round_trip(puzzle in puzzles()) {
+        let parsed = Grid::try_from_str(&puzzle.to_str()).unwrap();
+        prop_assert_eq!(parsed.to_str(), puzzle.to_str());
+        prop_assert_eq!(&parsed, &puzzle);
+
+        let mut solution = puzzle.clone();
+        prop_assert!(solution.solve().is_ok());
+        prop_assert_eq!(Grid::try_from_str(&solution.to_str()).unwrap().to_str(), solution.to_str());
+    }
+}